@@ -0,0 +1,133 @@
+use chrono::{DateTime, FixedOffset, TimeDelta};
+use std::fmt;
+
+use crate::duration::TimeSpent;
+use crate::model::WorklogRecord;
+
+/// Time worked on item, from started date for its time spent
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Interval {
+    pub id: String,
+    pub ticket: String,
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+}
+
+/// Two items booking same time, minutes of time booked twice
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Overlap {
+    pub first: Interval,
+    pub second: Interval,
+    pub minutes: u32,
+}
+
+/// Time of working day without items
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Gap {
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    pub minutes: u32,
+}
+
+impl Interval {
+    pub fn new(item: &WorklogRecord, minutes: u32) -> Self {
+        Interval {
+            id: item.id.clone(),
+            ticket: item.ticket.clone(),
+            start: item.started_date,
+            end: item.started_date + TimeDelta::minutes(minutes as i64),
+        }
+    }
+}
+
+/// Overlapping pairs of intervals, earlier started first
+pub fn find_overlaps(intervals: &[Interval]) -> Vec<Overlap> {
+    let mut sorted = intervals.to_vec();
+    sorted.sort_by_key(|v| v.start);
+
+    let mut overlaps = Vec::new();
+    for (i, first) in sorted.iter().enumerate() {
+        for second in sorted[i + 1..].iter().take_while(|v| v.start < first.end) {
+            let minutes = (first.end.min(second.end) - second.start).num_minutes();
+
+            if minutes > 0 {
+                overlaps.push(Overlap {
+                    first: first.clone(),
+                    second: second.clone(),
+                    minutes: minutes as u32,
+                });
+            }
+        }
+    }
+
+    overlaps
+}
+
+/// Parts of [from, to) not covered by any interval, shorter than a minute are ignored
+pub fn find_gaps(intervals: &[Interval], from: &DateTime<FixedOffset>, to: &DateTime<FixedOffset>) -> Vec<Gap> {
+    let mut sorted = intervals.to_vec();
+    sorted.sort_by_key(|v| v.start);
+
+    let mut gaps = Vec::new();
+    let mut covered_until = *from;
+    let mut push_gap = |start: DateTime<FixedOffset>, end: DateTime<FixedOffset>| {
+        let minutes = (end - start).num_minutes();
+        if minutes > 0 {
+            gaps.push(Gap {
+                start,
+                end,
+                minutes: minutes as u32,
+            });
+        }
+    };
+
+    for interval in sorted.iter().filter(|v| v.end > *from && v.start < *to) {
+        if interval.start > covered_until {
+            push_gap(covered_until, interval.start);
+        }
+        covered_until = covered_until.max(interval.end);
+    }
+
+    if covered_until < *to {
+        push_gap(covered_until, *to);
+    }
+
+    gaps
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: ticket={}, {}-{}",
+            self.id,
+            self.ticket,
+            self.start.format("%Y-%m-%d %H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+impl fmt::Display for Overlap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} overlaps {} by {}",
+            self.second,
+            self.first,
+            TimeSpent::from_minutes(self.minutes)
+        )
+    }
+}
+
+impl fmt::Display for Gap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Gap {}-{}, {}",
+            self.start.format("%Y-%m-%d %H:%M"),
+            self.end.format("%H:%M"),
+            TimeSpent::from_minutes(self.minutes)
+        )
+    }
+}
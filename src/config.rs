@@ -0,0 +1,904 @@
+use chrono::NaiveTime;
+use java_properties::read;
+use java_properties::write;
+use regex::Regex;
+use reqwest::Url;
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::stdin;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::{stdout, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::duration::{TimeSpent, TrackingRules, WorkingTime};
+use crate::error::JiralogError;
+use crate::jira::{CredentialCheck, JiraClient, JiraUser};
+use crate::model::{ApiVersion, AuthType, Configuration, Rounding, ShortEntries, TokenStore, WorklogMessage};
+use crate::paths::{get_config_dir_path, get_config_path, CONFIG_FILE};
+use crate::token;
+
+/// Configuration that can not be loaded or changed, fixed by running configure
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Profile {profile} not configured")]
+    NotConfigured { profile: String },
+    #[error("Reading configuration {} failed: {reason}", path.display())]
+    Unreadable { path: PathBuf, reason: String },
+    #[error("Missing {key} for profile {profile}")]
+    MissingKey { key: &'static str, profile: String },
+    #[error("Invalid {key} {value}, {expected}")]
+    InvalidValue {
+        key: String,
+        value: String,
+        expected: String,
+    },
+    #[error("Invalid jira_url {url}, {reason}")]
+    InvalidUrl { url: String, reason: String },
+    #[error("Token for profile {profile} unavailable: {reason}")]
+    Token { profile: String, reason: String },
+    #[error("Unknown property {0}, use one of {list}", list = PROPERTIES.join(", "))]
+    UnknownProperty(String),
+    #[error("Invalid profile {0}, use letters, digits, '-' and '_'")]
+    InvalidProfile(String),
+    #[error("Could not verify configuration, {reason}")]
+    Unverified { reason: String },
+    #[error("Storing token for profile {profile} failed: {reason}")]
+    TokenNotStored { profile: String, reason: String },
+}
+
+impl ConfigError {
+    /// Command fixing the error
+    pub fn hint(&self) -> String {
+        let configure = match active_profile() {
+            Some(profile) => format!("jiralog --profile {} configure", profile),
+            None => "jiralog configure".to_string(),
+        };
+
+        match self {
+            ConfigError::InvalidValue { key, .. } => format!("run {} set {} <value>", configure, key),
+            ConfigError::InvalidUrl { .. } => format!("run {} set jira_url <url>", configure),
+            ConfigError::Unverified { .. } => format!("check jira_url or run {}", configure),
+            ConfigError::TokenNotStored { .. } => {
+                "give --token-store properties, or set JIRALOG_TOKEN_STORE=properties or JIRALOG_PASSPHRASE"
+                    .to_string()
+            }
+            ConfigError::UnknownProperty(_) => format!("run {} list", configure),
+            ConfigError::InvalidProfile(_) => "use profile like acme or client-1".to_string(),
+            _ => format!("run {}", configure),
+        }
+    }
+}
+
+/// Properties of configuration, each can be overridden with JIRALOG_<PROPERTY> environment variable
+pub static PROPERTIES: [&str; 21] = [
+    "user",
+    "token",
+    "token_store",
+    "token_command",
+    "jira_cloud_instance",
+    "jira_url",
+    "editor",
+    "api_version",
+    "auth_type",
+    "timeout_secs",
+    "connect_timeout_secs",
+    "max_retries",
+    "retry_backoff_ms",
+    "hours_per_day",
+    "days_per_week",
+    "day_start",
+    "rounding",
+    "rounding_minutes",
+    "minimum_minutes",
+    "short_entries",
+    "daily_target",
+];
+
+/// Tries to enter working Jira location and credentials before configure gives up
+static CONFIGURE_ATTEMPTS: u32 = 3;
+
+static PROFILE_OVERRIDE: OnceLock<String> = OnceLock::new();
+
+/// Name of profile using jiralog.properties
+pub static DEFAULT_PROFILE: &str = "default";
+
+/// Use given profile, wins over JIRALOG_PROFILE. Call before reading configuration.
+pub fn set_profile(profile: String) {
+    let _ = PROFILE_OVERRIDE.set(profile);
+}
+
+/// Profile from --profile or JIRALOG_PROFILE, None for default profile
+pub fn active_profile() -> Option<String> {
+    PROFILE_OVERRIDE
+        .get()
+        .cloned()
+        .or_else(|| env::var("JIRALOG_PROFILE").ok())
+        .filter(|v| !v.is_empty() && v != DEFAULT_PROFILE)
+}
+
+pub fn profile_name(profile: Option<&str>) -> &str {
+    profile.unwrap_or(DEFAULT_PROFILE)
+}
+
+pub fn validate_profile(profile: &str) -> Result<(), ConfigError> {
+    let re = Regex::new(r"^[A-Za-z0-9_-]+$").unwrap();
+
+    if re.is_match(profile) {
+        Ok(())
+    } else {
+        Err(ConfigError::InvalidProfile(profile.to_string()))
+    }
+}
+
+/// Configured profiles, default first when configured
+pub fn list_profiles() -> Result<Vec<String>, Box<dyn Error>> {
+    let config_dir = get_config_dir_path()?;
+    let mut profiles = Vec::new();
+
+    if !config_dir.exists() {
+        return Ok(profiles);
+    }
+
+    for entry in fs::read_dir(config_dir)? {
+        let file_name = entry?.file_name().to_string_lossy().to_string();
+
+        if file_name == CONFIG_FILE {
+            profiles.insert(0, DEFAULT_PROFILE.to_string());
+        } else if let Some(profile) = file_name
+            .strip_prefix("jiralog-")
+            .and_then(|v| v.strip_suffix(".properties"))
+        {
+            profiles.push(profile.to_string());
+        }
+    }
+
+    Ok(profiles)
+}
+
+/// Configuration of profile, JIRALOG_<PROPERTY> environment variables override properties file
+pub fn read_config(profile: Option<&str>) -> Result<Configuration, ConfigError> {
+    let mut config_map = read_properties(profile)?;
+    apply_env_overrides(&mut config_map);
+
+    if config_map.is_empty() {
+        return Err(ConfigError::NotConfigured {
+            profile: profile_name(profile).to_string(),
+        });
+    }
+
+    config_from_properties(&config_map, profile)
+}
+
+fn env_key(property: &str) -> String {
+    format!("JIRALOG_{}", property.to_uppercase())
+}
+
+fn env_override(property: &str) -> Option<String> {
+    env::var(env_key(property)).ok()
+}
+
+/// Token from environment wins over token_command and token_store of properties file
+fn apply_env_overrides(config_map: &mut HashMap<String, String>) {
+    for property in PROPERTIES {
+        if let Some(value) = env_override(property) {
+            config_map.insert(property.to_string(), value);
+        }
+    }
+
+    if env_override("token").is_some() {
+        if env_override("token_command").is_none() {
+            config_map.remove("token_command");
+        }
+        if env_override("token_store").is_none() {
+            config_map.insert("token_store".to_string(), TokenStore::Properties.to_string());
+        }
+    }
+}
+
+fn validate_key(property: &str) -> Result<(), ConfigError> {
+    if PROPERTIES.contains(&property) {
+        Ok(())
+    } else {
+        Err(ConfigError::UnknownProperty(property.to_string()))
+    }
+}
+
+fn validate_property(property: &str, value: &str) -> Result<(), ConfigError> {
+    validate_key(property)?;
+
+    match property {
+        "api_version" => parse_value::<ApiVersion>(property, value).map(drop),
+        "auth_type" => parse_value::<AuthType>(property, value).map(drop),
+        "token_store" => parse_value::<TokenStore>(property, value).map(drop),
+        "timeout_secs" | "connect_timeout_secs" | "retry_backoff_ms" => {
+            parse_number::<u64>(property, value).map(drop)
+        }
+        "max_retries" => parse_number::<u32>(property, value).map(drop),
+        "hours_per_day" => parse_length(property, value, 24.0).map(drop),
+        "days_per_week" => parse_length(property, value, 7.0).map(drop),
+        "day_start" => parse_day_start(value).map(drop),
+        "rounding" => parse_value::<Rounding>(property, value).map(drop),
+        "short_entries" => parse_value::<ShortEntries>(property, value).map(drop),
+        "rounding_minutes" | "minimum_minutes" => parse_minutes(property, value).map(drop),
+        "daily_target" => parse_daily_target(value, &WorkingTime::default()).map(drop),
+        "jira_cloud_instance" => cloud_url(value).map(drop),
+        "jira_url" => validate_url(value).map(drop),
+        _ => Ok(()),
+    }
+}
+
+fn parse_value<T>(key: &str, value: &str) -> Result<T, ConfigError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value.trim().parse().map_err(|err: T::Err| ConfigError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+        expected: err.to_string(),
+    })
+}
+
+fn parse_number<T: FromStr>(key: &str, value: &str) -> Result<T, ConfigError> {
+    value.trim().parse().map_err(|_| ConfigError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+        expected: "use whole number".to_string(),
+    })
+}
+
+/// Length of working day in hours or week in days, above zero and at most max
+fn parse_length(key: &str, value: &str, max: f64) -> Result<f64, ConfigError> {
+    match value.trim().parse::<f64>() {
+        Ok(length) if length > 0.0 && length <= max => Ok(length),
+        _ => Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            expected: format!("use number above 0 and at most {}, for example 7.5", max),
+        }),
+    }
+}
+
+fn parse_day_start(value: &str) -> Result<NaiveTime, ConfigError> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").map_err(|_| ConfigError::InvalidValue {
+        key: "day_start".to_string(),
+        value: value.to_string(),
+        expected: "use time H:MM, for example 8:30".to_string(),
+    })
+}
+
+fn working_time_from_properties(
+    config_map: &HashMap<String, String>,
+) -> Result<WorkingTime, ConfigError> {
+    let get = |key: &str| config_map.get(key).filter(|v| !v.trim().is_empty());
+    let default = WorkingTime::default();
+
+    Ok(WorkingTime {
+        hours_per_day: get("hours_per_day")
+            .map(|v| parse_length("hours_per_day", v, 24.0))
+            .transpose()?
+            .unwrap_or(default.hours_per_day),
+        days_per_week: get("days_per_week")
+            .map(|v| parse_length("days_per_week", v, 7.0))
+            .transpose()?
+            .unwrap_or(default.days_per_week),
+        day_start: get("day_start")
+            .map(|v| parse_day_start(v))
+            .transpose()?
+            .unwrap_or(default.day_start),
+    })
+}
+
+/// Minutes of tracking rules, zero would round or keep items to empty worklogs
+fn parse_minutes(key: &str, value: &str) -> Result<u32, ConfigError> {
+    match parse_number::<u32>(key, value)? {
+        0 => Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            expected: "use whole number above 0".to_string(),
+        }),
+        minutes => Ok(minutes),
+    }
+}
+
+fn tracking_rules_from_properties(
+    config_map: &HashMap<String, String>,
+) -> Result<TrackingRules, ConfigError> {
+    let get = |key: &str| config_map.get(key).filter(|v| !v.trim().is_empty());
+    let default = TrackingRules::default();
+
+    Ok(TrackingRules {
+        rounding: get("rounding")
+            .map(|v| parse_value("rounding", v))
+            .transpose()?
+            .unwrap_or(default.rounding),
+        rounding_minutes: get("rounding_minutes")
+            .map(|v| parse_minutes("rounding_minutes", v))
+            .transpose()?
+            .unwrap_or(default.rounding_minutes),
+        minimum_minutes: get("minimum_minutes")
+            .map(|v| parse_minutes("minimum_minutes", v))
+            .transpose()?
+            .unwrap_or(default.minimum_minutes),
+        short_entries: get("short_entries")
+            .map(|v| parse_value("short_entries", v))
+            .transpose()?
+            .unwrap_or(default.short_entries),
+    })
+}
+
+/// Rounding and minimum of tracked time spent of profile without resolving token
+pub fn read_tracking_rules(profile: Option<&str>) -> Result<TrackingRules, ConfigError> {
+    let mut config_map = read_properties(profile)?;
+    apply_env_overrides(&mut config_map);
+
+    tracking_rules_from_properties(&config_map)
+}
+
+fn parse_daily_target(value: &str, working_time: &WorkingTime) -> Result<TimeSpent, ConfigError> {
+    TimeSpent::parse(value, working_time).map_err(|_| ConfigError::InvalidValue {
+        key: "daily_target".to_string(),
+        value: value.to_string(),
+        expected: "use time spent, for example 7h 30m".to_string(),
+    })
+}
+
+/// Time spent expected per working day, length of working day when not configured
+pub fn read_daily_target(profile: Option<&str>) -> Result<TimeSpent, ConfigError> {
+    let mut config_map = read_properties(profile)?;
+    apply_env_overrides(&mut config_map);
+
+    let working_time = working_time_from_properties(&config_map)?;
+
+    match config_map.get("daily_target").filter(|v| !v.trim().is_empty()) {
+        Some(value) => parse_daily_target(value, &working_time),
+        None => Ok(TimeSpent::from_minutes((working_time.hours_per_day * 60.0).round() as u32)),
+    }
+}
+
+/// Working day and week of profile without resolving token, Jira defaults when not configured
+pub fn read_working_time(profile: Option<&str>) -> Result<WorkingTime, ConfigError> {
+    let mut config_map = read_properties(profile)?;
+    apply_env_overrides(&mut config_map);
+
+    working_time_from_properties(&config_map)
+}
+
+fn cloud_url(instance: &str) -> Result<String, ConfigError> {
+    let re = Regex::new(r"^[A-Za-z0-9-]+$").unwrap();
+
+    if re.is_match(instance.trim()) {
+        Ok(format!("https://{}.atlassian.net", instance.trim()))
+    } else {
+        Err(ConfigError::InvalidValue {
+            key: "jira_cloud_instance".to_string(),
+            value: instance.to_string(),
+            expected: "use instance name, for example my-instance for my-instance.atlassian.net"
+                .to_string(),
+        })
+    }
+}
+
+/// Absolute http or https url without trailing slash
+fn validate_url(url: &str) -> Result<String, ConfigError> {
+    let invalid = |reason: String| ConfigError::InvalidUrl {
+        url: url.to_string(),
+        reason,
+    };
+
+    let parsed = Url::parse(url.trim()).map_err(|err| invalid(err.to_string()))?;
+
+    match parsed.scheme() {
+        "http" | "https" if parsed.has_host() => Ok(url.trim().trim_end_matches('/').to_string()),
+        "http" | "https" => Err(invalid("host missing".to_string())),
+        _ => Err(invalid("use http or https".to_string())),
+    }
+}
+
+/// Properties of profile, empty when profile is not configured yet
+fn read_properties(profile: Option<&str>) -> Result<HashMap<String, String>, ConfigError> {
+    let config_path = get_config_path(profile).map_err(|err| ConfigError::Unreadable {
+        path: PathBuf::new(),
+        reason: err.to_string(),
+    })?;
+
+    if !config_path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let unreadable = |reason: String| ConfigError::Unreadable {
+        path: config_path.clone(),
+        reason,
+    };
+    let file = File::open(&config_path).map_err(|err| unreadable(err.to_string()))?;
+
+    read(BufReader::new(file)).map_err(|err| unreadable(err.to_string()))
+}
+
+fn config_from_properties(
+    config_map: &HashMap<String, String>,
+    profile: Option<&str>,
+) -> Result<Configuration, ConfigError> {
+    let get = |key: &str| config_map.get(key).filter(|v| !v.trim().is_empty());
+    let missing = |key: &'static str| ConfigError::MissingKey {
+        key,
+        profile: profile_name(profile).to_string(),
+    };
+    let token_error = |err: Box<dyn Error>| ConfigError::Token {
+        profile: profile_name(profile).to_string(),
+        reason: err.to_string(),
+    };
+
+    // Keyring by default, plain token of earlier versions is read from properties
+    let token_store = get("token_store")
+        .map(|v| parse_value("token_store", v))
+        .transpose()?
+        .unwrap_or(if get("token").is_some() {
+            TokenStore::Properties
+        } else {
+            TokenStore::Keyring
+        });
+    let token = match (get("token_command"), token_store) {
+        (Some(command), _) => token::run_token_command(command).map_err(token_error)?,
+        (None, TokenStore::Properties) => get("token").cloned().ok_or_else(|| missing("token"))?,
+        (None, TokenStore::Keyring) => token::read_keyring(profile).map_err(token_error)?,
+        (None, TokenStore::File) => token::read_encrypted(profile).map_err(token_error)?,
+    };
+
+    // Cloud instance wins over url when both are configured
+    let jira_url = match (get("jira_cloud_instance"), get("jira_url")) {
+        (Some(instance), _) => cloud_url(instance)?,
+        (None, Some(url)) => validate_url(url)?,
+        (None, None) => return Err(missing("jira_url")),
+    };
+    let user = get("user");
+    let editor = get("editor");
+    let api_version = get("api_version")
+        .map(|v| parse_value("api_version", v))
+        .transpose()?
+        .unwrap_or(ApiVersion::V3);
+    let auth_type = get("auth_type")
+        .map(|v| parse_value("auth_type", v))
+        .transpose()?
+        .unwrap_or(AuthType::Basic);
+
+    let number = |key: &str, default: u64| -> Result<u64, ConfigError> {
+        Ok(get(key).map(|v| parse_number(key, v)).transpose()?.unwrap_or(default))
+    };
+    let timeout = Duration::from_secs(number("timeout_secs", 30)?);
+    let connect_timeout = Duration::from_secs(number("connect_timeout_secs", 10)?);
+    let max_retries = get("max_retries")
+        .map(|v| parse_number("max_retries", v))
+        .transpose()?
+        .unwrap_or(3);
+    let retry_backoff = Duration::from_millis(number("retry_backoff_ms", 500)?);
+    let working_time = working_time_from_properties(config_map)?;
+
+    if auth_type == AuthType::Basic && user.is_none() {
+        return Err(missing("user"));
+    }
+
+    Ok(Configuration {
+        profile: profile.map(String::from),
+        token,
+        jira_url,
+        user: user.cloned(),
+        editor: editor.cloned(),
+        api_version,
+        auth_type,
+        timeout,
+        connect_timeout,
+        max_retries,
+        retry_backoff,
+        working_time,
+    })
+}
+
+fn read_stdin(msg: &str) -> Result<String, Box<dyn Error>> {
+    print!("{}", msg);
+    stdout().flush()?;
+
+    let mut input = String::new();
+    stdin().read_line(&mut input)?;
+
+    Ok(input.trim().to_string())
+}
+
+/// Prompt showing current value, empty input keeps it
+fn read_stdin_or(msg: &str, current: Option<&String>) -> Result<Option<String>, Box<dyn Error>> {
+    let input = match current {
+        Some(value) => read_stdin(&format!("{} [{}]: ", msg, value))?,
+        None => read_stdin(&format!("{}: ", msg))?,
+    };
+
+    Ok(if input.is_empty() { current.cloned() } else { Some(input) })
+}
+
+/// Prompt for profile, Jira location, credentials and editor, verifying credentials against Jira
+pub fn configure() -> Result<WorklogMessage, Box<dyn Error>> {
+    let active = active_profile();
+    let profile = read_stdin_or("Profile", Some(&profile_name(active.as_deref()).to_string()))?
+        .filter(|v| v != DEFAULT_PROFILE);
+
+    if let Some(profile) = &profile {
+        validate_profile(profile)?;
+    }
+
+    let mut config_map = read_properties(profile.as_deref())?;
+    let (mut ask_location, mut ask_credentials) = (true, true);
+    let mut token = String::new();
+    let mut verified = false;
+
+    for attempt in 1..=CONFIGURE_ATTEMPTS {
+        if ask_location {
+            prompt_property(
+                &mut config_map,
+                "jira_cloud_instance",
+                "Jira cloud instance, empty for Jira url, '-' clears instance",
+            )?;
+            if config_map.get("jira_cloud_instance").is_some_and(|v| v == "-") {
+                config_map.remove("jira_cloud_instance");
+            }
+            if !config_map.contains_key("jira_cloud_instance") {
+                prompt_property(&mut config_map, "jira_url", "Jira url")?;
+                if let Some(url) = config_map.get_mut("jira_url") {
+                    *url = url.trim_end_matches('/').to_string();
+                }
+            }
+        }
+        if ask_credentials {
+            prompt_property(&mut config_map, "user", "Jira user")?;
+            token = token::prompt_secret("Jira token: ")?;
+        }
+
+        let mut candidate = config_map.clone();
+        candidate.remove("token_command");
+        candidate.insert("token_store".to_string(), TokenStore::Properties.to_string());
+        candidate.insert("token".to_string(), token.clone());
+
+        let mut config = match config_from_properties(&candidate, profile.as_deref()) {
+            Ok(config) => config,
+            Err(err) if attempt < CONFIGURE_ATTEMPTS => {
+                println!("{}, try again", err);
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        match check_credentials(&mut config, &mut config_map)? {
+            CredentialCheck::Valid(user) => {
+                print_authenticated(&user);
+                update_working_time(&JiraClient::new(&config)?, &mut config_map);
+                verified = true;
+                break;
+            }
+            CredentialCheck::Unauthorized if attempt < CONFIGURE_ATTEMPTS => {
+                println!("Jira rejected user or token, try again");
+                (ask_location, ask_credentials) = (false, true);
+            }
+            CredentialCheck::NotFound if attempt < CONFIGURE_ATTEMPTS => {
+                println!("Jira not found at {}, try again", config.jira_url);
+                (ask_location, ask_credentials) = (true, false);
+            }
+            CredentialCheck::Unauthorized => return Err(Box::new(rejected_credentials())),
+            CredentialCheck::NotFound => return Err(jira_not_found(&config.jira_url).into()),
+        }
+    }
+
+    if !verified {
+        return Err(ConfigError::Unverified {
+            reason: "nothing written".to_string(),
+        }
+        .into());
+    }
+
+    prompt_property(&mut config_map, "editor", "Editor, empty for $EDITOR")?;
+
+    store_token(&mut config_map, profile.as_deref(), token, true)?;
+
+    write_properties(profile.as_deref(), &config_map)
+}
+
+/// Check credentials against Jira. Server and data center without REST api 3 are checked
+/// again with api 2 unless api_version is configured, api 2 is kept when Jira answers to it.
+fn check_credentials(
+    config: &mut Configuration,
+    config_map: &mut HashMap<String, String>,
+) -> Result<CredentialCheck, Box<dyn Error>> {
+    let check = JiraClient::new(config)?.check_credentials()?;
+    let api_version_set = config_map.contains_key("api_version") || env_override("api_version").is_some();
+
+    if !matches!(check, CredentialCheck::NotFound) || api_version_set || config.api_version == ApiVersion::V2 {
+        return Ok(check);
+    }
+
+    config.api_version = ApiVersion::V2;
+    let check = JiraClient::new(config)?.check_credentials()?;
+
+    if matches!(check, CredentialCheck::NotFound) {
+        config.api_version = ApiVersion::V3;
+    } else {
+        config_map.insert("api_version".to_string(), ApiVersion::V2.to_string());
+    }
+
+    Ok(check)
+}
+
+fn rejected_credentials() -> JiralogError {
+    JiralogError::Jira {
+        status: 401,
+        message: "Jira rejected user or token".to_string(),
+    }
+}
+
+fn jira_not_found(jira_url: &str) -> ConfigError {
+    ConfigError::Unverified {
+        reason: format!("Jira not found at {}", jira_url),
+    }
+}
+
+/// Values given as configure flags
+pub struct ConfigureOptions {
+    pub user: Option<String>,
+    pub token: Option<String>,
+    pub instance: Option<String>,
+    pub url: Option<String>,
+    pub editor: Option<String>,
+    pub token_store: Option<TokenStore>,
+    pub verify: bool,
+}
+
+/// Configure active profile from flags without prompts, unset values are kept.
+/// Credentials are verified against Jira unless verify is off.
+pub fn configure_with(options: ConfigureOptions) -> Result<WorklogMessage, Box<dyn Error>> {
+    let profile = active_profile();
+    let mut config_map = read_properties(profile.as_deref())?;
+
+    if let Some(user) = options.user {
+        config_map.insert("user".to_string(), user);
+    }
+    if let Some(editor) = options.editor {
+        config_map.insert("editor".to_string(), editor);
+    }
+    if let Some(token_store) = options.token_store {
+        config_map.insert("token_store".to_string(), token_store.to_string());
+    }
+    // Cloud instance wins over url, keep only the one given
+    if let Some(instance) = options.instance {
+        config_map.remove("jira_url");
+        config_map.insert("jira_cloud_instance".to_string(), instance);
+    }
+    if let Some(url) = options.url {
+        config_map.remove("jira_cloud_instance");
+        config_map.insert("jira_url".to_string(), url.trim_end_matches('/').to_string());
+    }
+
+    if options.verify {
+        let mut candidate = config_map.clone();
+
+        if let Some(token) = &options.token {
+            candidate.remove("token_command");
+            candidate.insert("token_store".to_string(), TokenStore::Properties.to_string());
+            candidate.insert("token".to_string(), token.clone());
+        }
+
+        let mut config = config_from_properties(&candidate, profile.as_deref())?;
+
+        match check_credentials(&mut config, &mut config_map)? {
+            CredentialCheck::Valid(user) => {
+                print_authenticated(&user);
+                update_working_time(&JiraClient::new(&config)?, &mut config_map);
+            }
+            CredentialCheck::Unauthorized => return Err(Box::new(rejected_credentials())),
+            CredentialCheck::NotFound => return Err(jira_not_found(&config.jira_url).into()),
+        }
+    }
+
+    if let Some(token) = options.token {
+        store_token(&mut config_map, profile.as_deref(), token, false)?;
+    }
+
+    write_properties(profile.as_deref(), &config_map)
+}
+
+/// Fetch working day and week of active profile from Jira time tracking settings
+pub fn fetch_working_time() -> Result<WorklogMessage, Box<dyn Error>> {
+    let profile = active_profile();
+    let config = read_config(profile.as_deref())?;
+    let working_time = JiraClient::new(&config)?.working_time()?;
+
+    let mut config_map = read_properties(profile.as_deref())?;
+    insert_working_time(&mut config_map, &working_time);
+    write_properties(profile.as_deref(), &config_map)?;
+
+    Ok(WorklogMessage(format!(
+        "Working time of profile {}: {}h per day, {}d per week",
+        profile_name(profile.as_deref()),
+        working_time.hours_per_day,
+        working_time.days_per_week
+    )))
+}
+
+/// Take working time from Jira when readable, configured values are kept otherwise
+fn update_working_time(client: &JiraClient, config_map: &mut HashMap<String, String>) {
+    match client.working_time() {
+        Ok(working_time) => {
+            println!(
+                "Working time from Jira: {}h per day, {}d per week",
+                working_time.hours_per_day, working_time.days_per_week
+            );
+            insert_working_time(config_map, &working_time);
+        }
+        Err(err) => println!("Could not read working time from Jira ({}), keeping configured values", err),
+    }
+}
+
+fn insert_working_time(config_map: &mut HashMap<String, String>, working_time: &WorkingTime) {
+    config_map.insert("hours_per_day".to_string(), working_time.hours_per_day.to_string());
+    config_map.insert("days_per_week".to_string(), working_time.days_per_week.to_string());
+}
+
+/// Property of active profile, token is redacted
+pub fn get_property(property: &str) -> Result<WorklogMessage, Box<dyn Error>> {
+    validate_key(property)?;
+
+    let profile = active_profile();
+    let value = env_override(property)
+        .or_else(|| read_properties(profile.as_deref()).ok()?.remove(property))
+        .ok_or_else(|| format!("{} not set for profile {}", property, profile_name(profile.as_deref())))?;
+
+    Ok(WorklogMessage(format!("{}\n", redact(property, &value))))
+}
+
+/// Set property of active profile, token goes to configured token store
+pub fn set_property(property: &str, value: &str) -> Result<WorklogMessage, Box<dyn Error>> {
+    validate_property(property, value)?;
+
+    let profile = active_profile();
+    let mut config_map = read_properties(profile.as_deref())?;
+
+    if property == "token" {
+        store_token(&mut config_map, profile.as_deref(), value.to_string(), true)?;
+    } else {
+        config_map.insert(property.to_string(), value.to_string());
+    }
+
+    write_properties(profile.as_deref(), &config_map)?;
+
+    Ok(WorklogMessage(format!(
+        "Set {} for profile {}",
+        property,
+        profile_name(profile.as_deref())
+    )))
+}
+
+/// Properties of active profile, token redacted and environment overrides marked
+pub fn list_properties() -> Result<WorklogMessage, Box<dyn Error>> {
+    let config_map = read_properties(active_profile().as_deref())?;
+    let mut lines = Vec::new();
+
+    for property in PROPERTIES {
+        match (env_override(property), config_map.get(property)) {
+            (Some(value), _) => lines.push(format!(
+                "{}={} ({})",
+                property,
+                redact(property, &value),
+                env_key(property)
+            )),
+            (None, Some(value)) => lines.push(format!("{}={}", property, redact(property, value))),
+            (None, None) => (),
+        }
+    }
+
+    Ok(WorklogMessage(lines.iter().map(|v| format!("{}\n", v)).collect()))
+}
+
+fn redact(property: &str, value: &str) -> String {
+    if property == "token" {
+        "<redacted>".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn print_authenticated(user: &JiraUser) {
+    println!(
+        "Authenticated as {} ({})",
+        user.display_name.as_deref().unwrap_or_default(),
+        user.account_id.as_ref().or(user.key.as_ref()).map(String::as_str).unwrap_or_default()
+    );
+}
+
+/// Store token to keyring, encrypted file or properties as selected by JIRALOG_TOKEN_STORE or
+/// token_store property, keyring by default with encrypted file as fallback
+fn store_token(
+    config_map: &mut HashMap<String, String>,
+    profile: Option<&str>,
+    token: String,
+    prompt: bool,
+) -> Result<(), Box<dyn Error>> {
+    let token_store = env_override("token_store")
+        .or_else(|| config_map.get("token_store").filter(|v| !v.trim().is_empty()).cloned())
+        .map(|v| parse_value::<TokenStore>("token_store", &v))
+        .transpose()?
+        .unwrap_or(TokenStore::Keyring);
+
+    // Without prompt encrypted file needs passphrase from environment
+    let check_passphrase = |reason: &str| {
+        if prompt || token::has_passphrase_env() {
+            return Ok(());
+        }
+
+        Err(ConfigError::TokenNotStored {
+            profile: profile_name(profile).to_string(),
+            reason: format!("{}, encrypted token file needs passphrase", reason),
+        })
+    };
+
+    config_map.remove("token");
+    config_map.remove("token_command");
+
+    let token_store = match token_store {
+        TokenStore::Properties => {
+            config_map.insert("token".to_string(), token);
+            TokenStore::Properties
+        }
+        TokenStore::Keyring => match token::write_keyring(profile, &token) {
+            Ok(_) => TokenStore::Keyring,
+            Err(err) => {
+                check_passphrase(&format!("keyring unavailable ({})", err))?;
+                eprintln!("Keyring unavailable ({}), storing token in encrypted file", err);
+                token::write_encrypted(profile, &token)?;
+                TokenStore::File
+            }
+        },
+        TokenStore::File => {
+            check_passphrase("token_store is file")?;
+            token::write_encrypted(profile, &token)?;
+            TokenStore::File
+        }
+    };
+    config_map.insert("token_store".to_string(), token_store.to_string());
+
+    Ok(())
+}
+
+fn write_properties(
+    profile: Option<&str>,
+    config_map: &HashMap<String, String>,
+) -> Result<WorklogMessage, Box<dyn Error>> {
+    let jiralog_dir = get_config_dir_path()?;
+
+    if !jiralog_dir.exists() {
+        fs::create_dir_all(&jiralog_dir)?;
+    }
+
+    let config_path = get_config_path(profile)?;
+    let file = File::create(&config_path)?;
+    write(BufWriter::new(file), config_map)?;
+
+    Ok(WorklogMessage(format!(
+        "All good! Wrote {}",
+        config_path.display()
+    )))
+}
+
+/// Prompt for property showing current value, empty input keeps current value
+fn prompt_property(
+    config_map: &mut HashMap<String, String>,
+    key: &str,
+    msg: &str,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(value) = read_stdin_or(msg, config_map.get(key))? {
+        config_map.insert(key.to_string(), value);
+    }
+
+    Ok(())
+}
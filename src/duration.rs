@@ -0,0 +1,230 @@
+use chrono::{Datelike, NaiveDate, NaiveTime};
+use regex::Regex;
+use std::fmt;
+
+use crate::error::JiralogError;
+use crate::model::{Rounding, ShortEntries};
+
+static TIME_SPENT_FORMATS: &str = "use for example 1h 30m, 1.5h, 90min or 1:30";
+
+/// Time spent on work item as whole minutes, rendered in Jira format like 1h 30m
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TimeSpent {
+    minutes: u32,
+}
+
+/// Length of working day and week, Jira converts d and w units with these.
+/// Day start is used for dates given without time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorkingTime {
+    pub hours_per_day: f64,
+    pub days_per_week: f64,
+    pub day_start: NaiveTime,
+}
+
+/// Rounding and minimum for time spent tracked with begin and end
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackingRules {
+    pub rounding: Rounding,
+    pub rounding_minutes: u32,
+    pub minimum_minutes: u32,
+    pub short_entries: ShortEntries,
+}
+
+/// Units from largest to smallest, units in input must follow this order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Unit {
+    Week,
+    Day,
+    Hour,
+    Minute,
+}
+
+impl TimeSpent {
+    pub fn from_minutes(minutes: u32) -> Self {
+        TimeSpent { minutes }
+    }
+
+    pub fn minutes(&self) -> u32 {
+        self.minutes
+    }
+
+    /// Parse units like 1w 2d 1h 30m, decimals like 1.5h or clock like 1:30.
+    /// Units must be given once, largest first, and total must be whole minutes above zero.
+    pub fn parse(input: &str, working_time: &WorkingTime) -> Result<Self, JiralogError> {
+        let invalid = |reason: &str| {
+            JiralogError::Validation(format!(
+                "Invalid time spent {}, {}, {}",
+                input, reason, TIME_SPENT_FORMATS
+            ))
+        };
+        let value = input.trim();
+
+        let minutes = if let Some((hours, minutes)) = value.split_once(':') {
+            let clock = Regex::new(r"^\d+$").unwrap();
+            if !clock.is_match(hours) || !clock.is_match(minutes) || minutes.len() != 2 {
+                return Err(invalid("clock must be H:MM"));
+            }
+
+            let minutes: u32 = minutes.parse().map_err(|_| invalid("too large"))?;
+            if minutes >= 60 {
+                return Err(invalid("minutes of clock must be below 60"));
+            }
+
+            hours
+                .parse::<u32>()
+                .ok()
+                .and_then(|hours| hours.checked_mul(60))
+                .and_then(|hours| hours.checked_add(minutes))
+                .ok_or_else(|| invalid("too large"))?
+        } else {
+            let whole = Regex::new(r"^(\s*\d+(\.\d+)?\s*[A-Za-z]+)+\s*$").unwrap();
+            if !whole.is_match(value) {
+                return Err(invalid("unknown format"));
+            }
+
+            let part = Regex::new(r"(\d+(?:\.\d+)?)\s*([A-Za-z]+)").unwrap();
+            let mut previous: Option<Unit> = None;
+            let mut total = 0.0;
+
+            for captures in part.captures_iter(value) {
+                let unit = Unit::parse(&captures[2])
+                    .ok_or_else(|| invalid(&format!("unknown unit {}", &captures[2])))?;
+
+                match previous {
+                    Some(previous) if previous == unit => {
+                        return Err(invalid(&format!("unit {} given twice", &captures[2])))
+                    }
+                    Some(previous) if previous > unit => {
+                        return Err(invalid("give units from largest to smallest"))
+                    }
+                    _ => previous = Some(unit),
+                }
+
+                let amount: f64 = captures[1].parse().map_err(|_| invalid("invalid number"))?;
+                total += amount * unit.minutes(working_time);
+            }
+
+            if !total.is_finite() || total > u32::MAX as f64 {
+                return Err(invalid("too large"));
+            }
+            if (total - total.round()).abs() > 1e-9 {
+                return Err(invalid("must be whole minutes"));
+            }
+
+            total.round() as u32
+        };
+
+        if minutes == 0 {
+            return Err(invalid("must be above zero, Jira rejects empty worklogs"));
+        }
+
+        Ok(TimeSpent::from_minutes(minutes))
+    }
+
+    /// Time spent in weeks, days, hours and minutes of working time, for summaries
+    pub fn in_working_time(&self, working_time: &WorkingTime) -> String {
+        let mut remaining = self.minutes;
+        let mut parts = Vec::new();
+
+        for (unit, name) in [(Unit::Week, "w"), (Unit::Day, "d"), (Unit::Hour, "h"), (Unit::Minute, "m")] {
+            let unit_minutes = unit.minutes(working_time).round().max(1.0) as u32;
+            let amount = remaining / unit_minutes;
+
+            if amount > 0 {
+                parts.push(format!("{}{}", amount, name));
+                remaining -= amount * unit_minutes;
+            }
+        }
+
+        if parts.is_empty() {
+            "0m".to_string()
+        } else {
+            parts.join(" ")
+        }
+    }
+}
+
+impl TrackingRules {
+    /// Minutes rounded to multiple of rounding minutes, minimum is not applied
+    pub fn round(&self, minutes: u32) -> u32 {
+        let step = self.rounding_minutes.max(1);
+
+        match self.rounding {
+            Rounding::None => minutes,
+            Rounding::Up => minutes.div_ceil(step).saturating_mul(step),
+            Rounding::Down => minutes / step * step,
+            Rounding::Nearest => (minutes + step / 2) / step * step,
+        }
+    }
+
+    pub fn is_short(&self, minutes: u32) -> bool {
+        minutes < self.minimum_minutes
+    }
+}
+
+/// No rounding and 1m minimum, Jira rejects 0m
+impl Default for TrackingRules {
+    fn default() -> Self {
+        TrackingRules {
+            rounding: Rounding::None,
+            rounding_minutes: 15,
+            minimum_minutes: 1,
+            short_entries: ShortEntries::Keep,
+        }
+    }
+}
+
+impl WorkingTime {
+    /// Whole working days of week, first days of week are working days
+    pub fn working_days(&self) -> u32 {
+        self.days_per_week.ceil().clamp(1.0, 7.0) as u32
+    }
+
+    pub fn is_working_day(&self, date: &NaiveDate) -> bool {
+        date.weekday().num_days_from_monday() < self.working_days()
+    }
+}
+
+/// Jira defaults, 8h day and 5d week, day starts at 9:00
+impl Default for WorkingTime {
+    fn default() -> Self {
+        WorkingTime {
+            hours_per_day: 8.0,
+            days_per_week: 5.0,
+            day_start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+        }
+    }
+}
+
+impl Unit {
+    fn parse(unit: &str) -> Option<Self> {
+        match unit.to_lowercase().as_str() {
+            "w" | "wk" | "week" | "weeks" => Some(Unit::Week),
+            "d" | "day" | "days" => Some(Unit::Day),
+            "h" | "hr" | "hrs" | "hour" | "hours" => Some(Unit::Hour),
+            "m" | "min" | "mins" | "minute" | "minutes" => Some(Unit::Minute),
+            _ => None,
+        }
+    }
+
+    fn minutes(&self, working_time: &WorkingTime) -> f64 {
+        match self {
+            Unit::Week => working_time.days_per_week * working_time.hours_per_day * 60.0,
+            Unit::Day => working_time.hours_per_day * 60.0,
+            Unit::Hour => 60.0,
+            Unit::Minute => 1.0,
+        }
+    }
+}
+
+/// Jira format in hours and minutes, same minutes regardless of Jira working day settings
+impl fmt::Display for TimeSpent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.minutes / 60, self.minutes % 60) {
+            (0, minutes) => write!(f, "{}m", minutes),
+            (hours, 0) => write!(f, "{}h", hours),
+            (hours, minutes) => write!(f, "{}h {}m", hours, minutes),
+        }
+    }
+}
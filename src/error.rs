@@ -0,0 +1,107 @@
+use std::error::Error;
+
+use crate::config::ConfigError;
+
+/// Exit codes by error category, documented in --help
+pub static EXIT_CODES: &str = "Exit codes:
+  0  Success
+  1  Other failure, for example reading worklog or running editor
+  2  Invalid input, for example time spent or dates
+  3  Worklog item not found
+  4  Missing or invalid configuration
+  5  Jira not reachable
+  6  Jira rejected request";
+
+/// Failure of a jiralog command, category decides exit code
+#[derive(Debug, thiserror::Error)]
+pub enum JiralogError {
+    #[error("{0}")]
+    Validation(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error("{0}")]
+    Network(String),
+    #[error("{message}")]
+    Jira { status: u16, message: String },
+    #[error("{failed} of {total} items failed, run commit again to retry failed items")]
+    Commit {
+        failed: usize,
+        total: usize,
+        /// First failure, decides exit code
+        cause: Box<JiralogError>,
+    },
+    #[error("{0}")]
+    Other(String),
+}
+
+impl JiralogError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            JiralogError::Other(_) => 1,
+            JiralogError::Validation(_) => 2,
+            JiralogError::NotFound(_) => 3,
+            JiralogError::Config(_) => 4,
+            JiralogError::Network(_) => 5,
+            JiralogError::Jira { .. } => 6,
+            JiralogError::Commit { cause, .. } => cause.exit_code(),
+        }
+    }
+
+    /// Same error with context prepended to message
+    pub fn context(self, context: &str) -> Self {
+        match self {
+            JiralogError::Validation(message) => {
+                JiralogError::Validation(format!("{}: {}", context, message))
+            }
+            JiralogError::NotFound(message) => {
+                JiralogError::NotFound(format!("{}: {}", context, message))
+            }
+            JiralogError::Network(message) => JiralogError::Network(format!("{}: {}", context, message)),
+            JiralogError::Jira { status, message } => JiralogError::Jira {
+                status,
+                message: format!("{}: {}", context, message),
+            },
+            JiralogError::Other(message) => JiralogError::Other(format!("{}: {}", context, message)),
+            other => other,
+        }
+    }
+}
+
+/// Errors of store, paths and editor, configuration errors keep their category
+impl From<Box<dyn Error>> for JiralogError {
+    fn from(err: Box<dyn Error>) -> Self {
+        match err.downcast::<ConfigError>() {
+            Ok(config_error) => JiralogError::Config(*config_error),
+            Err(err) => match err.downcast::<JiralogError>() {
+                Ok(jiralog_error) => *jiralog_error,
+                Err(err) => JiralogError::Other(err.to_string()),
+            },
+        }
+    }
+}
+
+impl From<std::io::Error> for JiralogError {
+    fn from(err: std::io::Error) -> Self {
+        JiralogError::Other(err.to_string())
+    }
+}
+
+impl From<csv::Error> for JiralogError {
+    fn from(err: csv::Error) -> Self {
+        JiralogError::Other(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for JiralogError {
+    fn from(err: serde_json::Error) -> Self {
+        JiralogError::Other(err.to_string())
+    }
+}
+
+impl From<reqwest::Error> for JiralogError {
+    fn from(err: reqwest::Error) -> Self {
+        JiralogError::Network(format!("Request failed with error: {}", err))
+    }
+}
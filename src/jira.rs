@@ -1,26 +1,415 @@
-use std::error::Error;
-use regex::Regex;
-
-use crate::model::WorklogRecord;
-
-pub fn update_time_spent(
-    jira_url: &str,
-    user: &str,
-    api_token: &str,
-    worklog: &WorklogRecord,
-) -> Result<(), Box<dyn Error>> {
-    let client = reqwest::blocking::Client::new();
-    let url = format!("{}/rest/api/3/issue/{}/worklog", jira_url, worklog.ticket);
-
-    let payload = serde_json::json!({
-        "timeSpent": worklog.time_spent,
-        "started": worklog.started_date.format("%Y-%m-%dT%H:%M:%S.%3f%z").to_string(),
-        "comment": {
+use crate::config::{profile_name, ConfigError};
+use crate::duration::{TimeSpent, WorkingTime};
+use crate::error::JiralogError;
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset, Utc};
+use http::{header::RETRY_AFTER, Method, StatusCode};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::model::{ApiVersion, AuthType, Configuration, WorklogRecord};
+
+/// Max worklog ids per worklog list request
+static WORKLOG_LIST_MAX_IDS: usize = 1000;
+
+/// Upper bound for a single retry wait, also caps Retry-After from Jira
+static MAX_RETRY_WAIT: Duration = Duration::from_secs(60);
+
+/// Jira user, cloud identifies users by account id, server by key
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JiraUser {
+    pub account_id: Option<String>,
+    pub key: Option<String>,
+    pub display_name: Option<String>,
+}
+
+/// Outcome of checking configured Jira location and credentials
+pub enum CredentialCheck {
+    Valid(JiraUser),
+    Unauthorized,
+    NotFound,
+}
+
+impl JiraUser {
+    pub fn is_same(&self, other: &JiraUser) -> bool {
+        match (&self.account_id, &other.account_id) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.key.is_some() && self.key == other.key,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteWorklog {
+    pub id: String,
+    pub issue_id: String,
+    pub author: JiraUser,
+    pub started: String,
+    pub time_spent: String,
+    /// Time spent as seconds, exact regardless of Jira working day settings
+    #[serde(default)]
+    pub time_spent_seconds: Option<u64>,
+    pub comment: Option<Value>,
+}
+
+impl RemoteWorklog {
+    pub fn started_date(&self) -> Result<DateTime<FixedOffset>, JiralogError> {
+        DateTime::parse_from_str(&self.started, "%Y-%m-%dT%H:%M:%S%.f%z").map_err(|err| {
+            JiralogError::Other(format!("Invalid started date {} from Jira: {}", self.started, err))
+        })
+    }
+
+    pub fn time_spent(&self, working_time: &WorkingTime) -> Result<TimeSpent, JiralogError> {
+        match self.time_spent_seconds {
+            Some(seconds) => Ok(TimeSpent::from_minutes((seconds / 60) as u32)),
+            None => TimeSpent::parse(&self.time_spent, working_time),
+        }
+    }
+
+    /// Comment as plain text, v3 comments are flattened from document format
+    pub fn description(&self) -> String {
+        self.comment.as_ref().map(comment_text).unwrap_or_default()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JiraConfiguration {
+    time_tracking_configuration: Option<TimeTrackingConfiguration>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TimeTrackingConfiguration {
+    working_hours_per_day: f64,
+    working_days_per_week: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdatedWorklogs {
+    values: Vec<UpdatedWorklog>,
+    until: i64,
+    last_page: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdatedWorklog {
+    worklog_id: i64,
+}
+
+/// Jira REST client sharing one connection pool, retries throttled and unavailable responses
+pub struct JiraClient<'a> {
+    config: &'a Configuration,
+    client: Client,
+}
+
+impl<'a> JiraClient<'a> {
+    pub fn new(config: &'a Configuration) -> Result<Self, JiralogError> {
+        let client = Client::builder()
+            .timeout(config.timeout)
+            .connect_timeout(config.connect_timeout)
+            .build()?;
+
+        Ok(JiraClient { config, client })
+    }
+
+    /// Add worklog to issue, returns id of created worklog
+    pub fn update_time_spent(&self, worklog: &WorklogRecord) -> Result<String, JiralogError> {
+        let url = self.worklog_url(&worklog.ticket);
+        let payload = self.worklog_payload(worklog);
+
+        let response = self.send(Method::POST, &url, Some(&payload))?;
+
+        match response.status() {
+            StatusCode::CREATED => {
+                let created: Value = response.json()?;
+
+                created["id"]
+                    .as_str()
+                    .map(|id| id.to_string())
+                    .ok_or_else(|| JiralogError::Other("Worklog id missing from response".to_string()))
+            }
+            _ => Err(response_error("Update", response)),
+        }
+    }
+
+    /// Request update_time_spent would send, credentials redacted
+    pub fn describe_update_time_spent(&self, worklog: &WorklogRecord) -> Result<String, JiralogError> {
+        let payload = serde_json::to_string_pretty(&self.worklog_payload(worklog))?;
+
+        Ok(format!(
+            "{} {}\n{}\n{}",
+            Method::POST,
+            self.worklog_url(&worklog.ticket),
+            self.redacted_authorization(),
+            payload
+        ))
+    }
+
+    /// Replace time spent, started date and comment of committed worklog
+    pub fn update_worklog(&self, worklog: &WorklogRecord, remote_id: &str) -> Result<(), JiralogError> {
+        let url = format!("{}/{}", self.worklog_url(&worklog.ticket), remote_id);
+        let payload = self.worklog_payload(worklog);
+
+        let response = self.send(Method::PUT, &url, Some(&payload))?;
+
+        match response.status() {
+            StatusCode::OK => Ok(()),
+            _ => Err(response_error("Update", response)),
+        }
+    }
+
+    pub fn delete_worklog(&self, ticket: &str, remote_id: &str) -> Result<(), JiralogError> {
+        let url = format!("{}/{}", self.worklog_url(ticket), remote_id);
+
+        let response = self.send(Method::DELETE, &url, None)?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT => Ok(()),
+            _ => Err(response_error("Delete", response)),
+        }
+    }
+
+    pub fn myself(&self) -> Result<JiraUser, JiralogError> {
+        let url = format!("{}/myself", self.api_url());
+
+        self.get_json(&url)
+    }
+
+    /// Working day and week from time tracking settings of global configuration, readable without
+    /// admin permission unlike configuration/timetracking/options
+    pub fn working_time(&self) -> Result<WorkingTime, JiralogError> {
+        let url = format!("{}/configuration", self.api_url());
+        let configuration: JiraConfiguration = self.get_json(&url)?;
+
+        configuration
+            .time_tracking_configuration
+            .map(|v| WorkingTime {
+                hours_per_day: v.working_hours_per_day,
+                days_per_week: v.working_days_per_week,
+                ..WorkingTime::default()
+            })
+            .ok_or_else(|| JiralogError::Other("Time tracking is disabled in Jira".to_string()))
+    }
+
+    /// Current user when credentials are accepted, distinguishes rejected credentials from unknown Jira
+    pub fn check_credentials(&self) -> Result<CredentialCheck, JiralogError> {
+        let url = format!("{}/myself", self.api_url());
+        let response = self.send(Method::GET, &url, None)?;
+
+        match response.status() {
+            StatusCode::OK => Ok(CredentialCheck::Valid(response.json()?)),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Ok(CredentialCheck::Unauthorized),
+            StatusCode::NOT_FOUND => Ok(CredentialCheck::NotFound),
+            _ => Err(response_error("Credential check", response)),
+        }
+    }
+
+    /// Ids of worklogs created or updated since given time
+    pub fn updated_worklog_ids(&self, since: &DateTime<FixedOffset>) -> Result<Vec<String>, JiralogError> {
+        let mut ids = Vec::new();
+        let mut since = since.timestamp_millis();
+
+        loop {
+            let url = format!("{}/worklog/updated?since={}", self.api_url(), since);
+            let page: UpdatedWorklogs = self.get_json(&url)?;
+
+            ids.extend(page.values.iter().map(|v| v.worklog_id.to_string()));
+
+            if page.last_page || page.values.is_empty() {
+                return Ok(ids);
+            }
+            since = page.until;
+        }
+    }
+
+    pub fn worklogs_by_ids(&self, ids: &[String]) -> Result<Vec<RemoteWorklog>, JiralogError> {
+        let url = format!("{}/worklog/list", self.api_url());
+        let mut worklogs = Vec::new();
+
+        for chunk in ids.chunks(WORKLOG_LIST_MAX_IDS) {
+            let payload = serde_json::json!({ "ids": chunk });
+            let response = self.send(Method::POST, &url, Some(&payload))?;
+
+            match response.status() {
+                StatusCode::OK => worklogs.extend(response.json::<Vec<RemoteWorklog>>()?),
+                _ => return Err(response_error("Worklog list", response)),
+            }
+        }
+
+        Ok(worklogs)
+    }
+
+    pub fn issue_key(&self, issue_id: &str) -> Result<String, JiralogError> {
+        let url = format!("{}/issue/{}?fields=key", self.api_url(), issue_id);
+        let issue: Value = self.get_json(&url)?;
+
+        issue["key"]
+            .as_str()
+            .map(|key| key.to_string())
+            .ok_or_else(|| JiralogError::Other(format!("Issue key missing for issue {}", issue_id)))
+    }
+
+    fn get_json<T: for<'de> Deserialize<'de>>(&self, url: &str) -> Result<T, JiralogError> {
+        let response = self.send(Method::GET, url, None)?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json()?),
+            _ => Err(response_error("Request", response)),
+        }
+    }
+
+    /// Send request, retrying with exponential backoff on 429 and 502-504 and on connection failures.
+    /// POST is retried only on 429 and 503 and connection failures, a timed out POST or one answered
+    /// with 502 or 504 may have been applied.
+    fn send(&self, method: Method, url: &str, payload: Option<&Value>) -> Result<Response, JiralogError> {
+        let mut attempt = 0;
+
+        loop {
+            let mut request = self
+                .authorize(self.client.request(method.clone(), url))?
+                .header("Accept", "application/json");
+
+            if let Some(payload) = payload {
+                request = request.json(payload);
+            }
+
+            let retries_left = attempt < self.config.max_retries;
+
+            match request.send() {
+                Ok(response) if retries_left && is_retryable_status(&method, response.status()) => {
+                    let wait = retry_after(&response).unwrap_or_else(|| self.backoff(attempt));
+                    thread::sleep(wait.min(MAX_RETRY_WAIT));
+                }
+                Ok(response) => return Ok(response),
+                Err(err) if retries_left && (err.is_connect() || (err.is_timeout() && method != Method::POST)) => {
+                    thread::sleep(self.backoff(attempt));
+                }
+                Err(err) => return Err(err.into()),
+            }
+
+            attempt += 1;
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.config
+            .retry_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(MAX_RETRY_WAIT)
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> Result<RequestBuilder, JiralogError> {
+        match self.config.auth_type {
+            AuthType::Basic => {
+                let user = self.config.user.as_ref().ok_or_else(|| ConfigError::MissingKey {
+                    key: "user",
+                    profile: profile_name(self.config.profile.as_deref()).to_string(),
+                })?;
+                Ok(request.basic_auth(user, Some(&self.config.token)))
+            }
+            AuthType::Bearer => Ok(request.bearer_auth(&self.config.token)),
+        }
+    }
+
+    fn redacted_authorization(&self) -> String {
+        match self.config.auth_type {
+            AuthType::Basic => format!(
+                "Authorization: Basic {}:<redacted>",
+                self.config.user.as_deref().unwrap_or_default()
+            ),
+            AuthType::Bearer => "Authorization: Bearer <redacted>".to_string(),
+        }
+    }
+
+    fn api_url(&self) -> String {
+        format!("{}/rest/api/{}", self.config.jira_url, self.config.api_version)
+    }
+
+    fn worklog_url(&self, ticket: &str) -> String {
+        format!("{}/issue/{}/worklog", self.api_url(), ticket)
+    }
+
+    fn worklog_payload(&self, worklog: &WorklogRecord) -> Value {
+        serde_json::json!({
+            "timeSpent": worklog.time_spent,
+            "started": worklog.started_date.format("%Y-%m-%dT%H:%M:%S.%3f%z").to_string(),
+            "comment": comment(self.config.api_version, &worklog.description),
+        })
+    }
+}
+
+/// Rejected before processing for all methods, gateway errors only for idempotent methods
+fn is_retryable_status(method: &Method, status: StatusCode) -> bool {
+    match status {
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => true,
+        StatusCode::BAD_GATEWAY | StatusCode::GATEWAY_TIMEOUT => *method != Method::POST,
+        _ => false,
+    }
+}
+
+/// Wait from Retry-After header, given either in seconds or as http date
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+
+    match value.trim().parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => {
+            let date = DateTime::parse_from_rfc2822(value).ok()?;
+            (date.with_timezone(&Utc) - Utc::now()).to_std().ok()
+        }
+    }
+}
+
+/// Error with status and Jira error messages from response body when available
+fn response_error(action: &str, response: Response) -> JiralogError {
+    let status = response.status();
+    let body: Option<Value> = response.json().ok();
+
+    let mut messages: Vec<String> = Vec::new();
+
+    if let Some(body) = body {
+        if let Some(error_messages) = body["errorMessages"].as_array() {
+            messages.extend(error_messages.iter().filter_map(|v| v.as_str()).map(String::from));
+        }
+        if let Some(errors) = body["errors"].as_object() {
+            messages.extend(
+                errors
+                    .iter()
+                    .map(|(field, message)| format!("{}: {}", field, message.as_str().unwrap_or_default())),
+            );
+        }
+    }
+
+    let message = if messages.is_empty() {
+        format!("{} failed with status: {}", action, status)
+    } else {
+        format!("{} failed with status: {}, {}", action, status, messages.join(", "))
+    };
+
+    JiralogError::Jira {
+        status: status.as_u16(),
+        message,
+    }
+}
+
+/// Api v3 takes comment in Atlassian document format, v2 in plain text
+fn comment(api_version: ApiVersion, description: &str) -> Value {
+    match api_version {
+        ApiVersion::V2 => Value::String(description.to_string()),
+        ApiVersion::V3 => serde_json::json!({
             "content": [
               {
                 "content": [
                   {
-                    "text": worklog.description,
+                    "text": description,
                     "type": "text"
                   }
                 ],
@@ -29,37 +418,30 @@ pub fn update_time_spent(
             ],
             "type": "doc",
             "version": 1
-          },
-    });
-
-    let response = client
-        .post(url)
-        .basic_auth(user, Some(api_token))
-        .header("Accept", "application/json")
-        .header("Content-Type", "application/json")
-        .json(&payload)
-        .send();
-
-    match response {
-        Ok(resp) if resp.status() == 201 => 
-            Ok(()),
-        Ok(resp) => 
-            Err(format!("Update failed with status: {}", resp.status()).into()),  
-        Err(err) => 
-            Err(format!("Request failed with error: {}", err).into())
+          }),
     }
 }
 
-pub fn validate_jira_time_spent(input: &str) -> Result<(), Box<dyn Error>> {
-    if input == "current" {
-        return Ok(())
+/// Text of plain comment or text nodes of document format comment, one line per paragraph
+fn comment_text(comment: &Value) -> String {
+    fn collect_text(node: &Value, text: &mut String) {
+        if let Some(value) = node["text"].as_str() {
+            text.push_str(value);
+        }
+        if let Some(content) = node["content"].as_array() {
+            content.iter().for_each(|child| collect_text(child, text));
+            if node["type"] == "paragraph" {
+                text.push('\n');
+            }
+        }
     }
 
-    let re = Regex::new(r"^(\d+[mdhw])+$").unwrap();
-
-    if re.is_match(input) {
-        Ok(())
-    } else {
-        Err("Invalid time spent, use jira time spent format, for example 1d5h".into())
+    match comment {
+        Value::String(value) => value.clone(),
+        document => {
+            let mut text = String::new();
+            collect_text(document, &mut text);
+            text.trim_end().to_string()
+        }
     }
-}
\ No newline at end of file
+}
@@ -1,23 +1,47 @@
 mod worklog;
+mod config;
 mod jira;
 mod model;
 mod editor;
+mod paths;
+mod store;
+mod token;
+mod error;
+mod duration;
+mod output;
+mod check;
+mod report;
 
-use chrono::Local;
+use chrono::{Datelike, DateTime, FixedOffset, Local, NaiveDate, TimeDelta};
 use csvlens::run_csvlens;
-use model::{WorklogMessage, WorklogRecord};
-use worklog::BeginWorklog;
+use duration::TimeSpent;
+use error::{JiralogError, EXIT_CODES};
+use model::{TokenStore, WorklogMessage};
+use output::OutputFormat;
+use report::GroupBy;
+use serde_json::{json, Value};
+use worklog::{BeginWorklog, CheckReport, EndOutcome, EndedWorklog};
 
-use std::error::Error;
+use std::path::PathBuf;
+use std::process::ExitCode;
 use inline_colorization::*;
 
 use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, after_help = EXIT_CODES)]
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+    /// Directory for configuration and worklog, overrides JIRALOG_HOME
+    #[arg(long, global = true)]
+    home: Option<PathBuf>,
+    /// Named profile for Jira instance, overrides JIRALOG_PROFILE
+    #[arg(long, global = true)]
+    profile: Option<String>,
+    /// Output format, json prints one document per command for scripts
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
 }
 
 /// Command line tool to update issue worklog in Jira
@@ -26,31 +50,68 @@ enum Commands {
     /// Add work item, by default started date is current time
     Add {
         ticket: String,
-        /// Time spent in Jira format, for example 1d5h
+        /// Time spent, for example 1h 30m, 1.5h, 90min or 1:30
         time_spent: String,
-        /// Provide start date for work item in format 'YYYY-MM-DDTHH:MM' or 'H:M'. H:M defaults to current day
-        #[arg(short, long)]
+        /// Provide start date for work item, for example '2024-12-07T09:30', '9:30', 'yesterday 9:00', 'mon 14:00',
+        /// 'last friday' or '-3d 10:00'. Date without time is start of working day
+        #[arg(short, long, allow_hyphen_values = true)]
         started_date: Option<String>,
         /// Add description for work
         #[arg(short, long)]
         description: Option<String>,
+        /// Refuse item overlapping other items instead of warning
+        #[arg(long)]
+        strict: bool,
     },
-    /// Remove work item
+    /// Remove work item, committed item is deleted from Jira too
     Rm {
         /// Item to remove
         id: String,
+        /// Remove from local worklog only, keep worklog in Jira
+        #[arg(short, long)]
+        local: bool,
+    },
+    /// Remove latest work item, committed item is deleted from Jira too
+    Pop {
+        /// Remove from local worklog only, keep worklog in Jira
+        #[arg(short, long)]
+        local: bool,
+    },
+    /// Edit work item, committed item is updated in Jira too
+    Edit {
+        /// Item to edit
+        id: String,
+        /// Time spent, for example 1h 30m, 1.5h, 90min or 1:30
+        #[arg(short, long)]
+        time_spent: Option<String>,
+        /// Start date for work item, for example '2024-12-07T09:30', '9:30', 'yesterday 9:00', 'mon 14:00',
+        /// 'last friday' or '-3d 10:00'. Date without time is start of working day
+        #[arg(short, long, allow_hyphen_values = true)]
+        started_date: Option<String>,
+        /// Description for work
+        #[arg(short, long)]
+        description: Option<String>,
     },
-    /// Remove latest work item
-    Pop {},
     /// Begin work item, ends previous work, records time automatically
     Begin {
         ticket: String,
         /// Add description for work
         #[arg(short, long)]
         description: Option<String>,
+        /// Begin at time instead of now, previous work ends at same time. For example '-20m', '15 minutes ago', 'yesterday 17:30' or '9:30'
+        #[arg(long, allow_hyphen_values = true)]
+        at: Option<String>,
     },
     /// End current work
-    End { },
+    End {
+        /// End at time instead of now. For example '-20m', '15 minutes ago', 'yesterday 17:30' or '9:30'
+        #[arg(long, allow_hyphen_values = true)]
+        at: Option<String>,
+    },
+    /// Pause current work, paused time is not counted in time spent
+    Pause {},
+    /// Resume paused current work
+    Resume {},
     /// Print current work item
     Current {
         /// Output format, ticket %ti, description %d, time spent %ts. Empty if current unavailable. For example -of "[%ti]"
@@ -58,89 +119,246 @@ enum Commands {
         format: Option<String>
     },
     /// Commit worklog to Jira
-    Commit {},
+    Commit {
+        /// Print requests that would be sent to Jira, worklog is left untouched
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+        /// Refuse commit when items overlap each other or committed items instead of warning
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Pull own worklogs from Jira into worklog as committed items
+    Pull {
+        /// First day to pull in format 'YYYY-MM-DD', defaults to 7 days ago
+        #[arg(short, long)]
+        from: Option<NaiveDate>,
+        /// Last day to pull in format 'YYYY-MM-DD', defaults to today
+        #[arg(short, long)]
+        to: Option<NaiveDate>,
+    },
     /// Remove committed entries from worklog
     Purge {},
-    /// Show worklog in explorer tui, optionally to stdout
+    /// Total time spent between days by day, week, ticket or project
+    Report {
+        /// First day, for example '2024-12-01', 'mon' or '-7d'. Defaults to start of current week
+        #[arg(short, long, allow_hyphen_values = true)]
+        from: Option<String>,
+        /// Last day, defaults to today
+        #[arg(short, long, allow_hyphen_values = true)]
+        to: Option<String>,
+        /// Group totals by day, week, ticket or project, days and weeks under daily target are highlighted
+        #[arg(short, long, value_enum, default_value_t = GroupBy::Day)]
+        group_by: GroupBy,
+    },
+    /// List overlapping items and gaps in working day
+    Check {
+        /// Day to check, for example '2024-12-07', 'yesterday' or 'mon'. Defaults to today
+        #[arg(allow_hyphen_values = true)]
+        day: Option<String>,
+    },
+    /// Show worklog in explorer tui, optionally to stdout. With --output json prints items to stdout
     Show {
         /// Output worklog to stdout
         #[arg(short, long)]
-        stdout: bool
+        stdout: bool,
+        /// Only items for ticket, with --stdout
+        #[arg(short, long, requires = "stdout")]
+        ticket: Option<String>,
+        /// Only items started on date in format 'YYYY-MM-DD', with --stdout
+        #[arg(short, long, requires = "stdout")]
+        date: Option<NaiveDate>,
+    },
+    /// Configure jiralog, prompts for values unless given as flags
+    #[command(args_conflicts_with_subcommands = true)]
+    Configure {
+        #[command(subcommand)]
+        action: Option<ConfigureAction>,
+        /// Jira user
+        #[arg(long)]
+        user: Option<String>,
+        /// Jira api token or personal access token
+        #[arg(long)]
+        token: Option<String>,
+        /// Jira cloud instance
+        #[arg(long, conflicts_with = "url")]
+        instance: Option<String>,
+        /// Jira url, for server and data center
+        #[arg(long)]
+        url: Option<String>,
+        /// Editor for commit
+        #[arg(long)]
+        editor: Option<String>,
+        /// Store token to keyring, file or properties, file needs JIRALOG_PASSPHRASE
+        #[arg(long, requires = "token")]
+        token_store: Option<TokenStore>,
+        /// Write flags without verifying credentials against Jira
+        #[arg(long)]
+        no_verify: bool,
     },
-    /// Configure jiralog
-    Configure {},
+    /// Migrate csv worklog to sqlite storage
+    Migrate {},
     /// Print info
     Info {},
 }
 
-fn main() {
+/// Read or change single configuration property of active profile
+#[derive(Subcommand)]
+enum ConfigureAction {
+    /// Print property, environment override wins
+    Get {
+        property: String,
+    },
+    /// Set property, token goes to configured token store
+    Set {
+        property: String,
+        value: String,
+    },
+    /// Print all properties
+    List {},
+    /// Fetch working day and week lengths from Jira time tracking settings
+    WorkingTime {},
+}
+
+fn main() -> ExitCode {
     let cli = Cli::parse();
 
+    output::set_output_format(cli.output);
+
+    if let Some(home) = cli.home {
+        paths::set_home(home);
+    }
+
+    if let Some(profile) = cli.profile {
+        config::set_profile(profile);
+    }
+
+    if let Some(Err(e)) = config::active_profile().map(|v| config::validate_profile(&v)) {
+        return run(|| Err::<(), _>(e), |_| String::new(), |_| Value::Null);
+    }
+
     match cli.command {
-        Some(Commands::Add { ticket, time_spent, description , started_date}) => {
+        Some(Commands::Add { ticket, time_spent, description , started_date, strict}) => {
             run(|| worklog::add(
             &ticket, 
                 &time_spent, 
                 &description.unwrap_or("".to_string()),
                              &started_date
-                    .map(|v| model::get_started_date(&v))
-                    .unwrap_or_else(|| Ok(Local::now().fixed_offset()))?
-            ), |added_item| format!(
+                    .map(|v| parse_date(&v))
+                    .unwrap_or_else(|| Ok(Local::now().fixed_offset()))?,
+                strict,
+            ), |added_worklog| format!(
                 "Added {}: ticket={}, time spent={}, started_date={}, description={}",
-                added_item.id,
-                added_item.ticket,
-                added_item.time_spent,
-                added_item.started_date,
-                added_item.description,
-            ));
+                added_worklog.added.id,
+                added_worklog.added.ticket,
+                added_worklog.added.time_spent,
+                added_worklog.added.started_date,
+                added_worklog.added.description,
+            ), |added_worklog| json!(added_worklog))
+        }
+        Some(Commands::Rm { id, local }) => {
+            run(
+                || worklog::remove(&id, local),
+                |removed_item| format!("Removed {}", removed_item.id),
+                |removed_item| json!({ "removed": removed_item }),
+            )
         }
-        Some(Commands::Rm { id }) => {
-            run(|| worklog::remove(&id), |id| format!("Removed {}", id));
+        Some(Commands::Edit { id, time_spent, started_date, description }) => {
+            run(|| worklog::edit(
+                &id,
+                &time_spent,
+                &started_date
+                    .map(|v| parse_date(&v))
+                    .transpose()?,
+                &description,
+            ), |edited_item| format!(
+                "Edited {}: ticket={}, time spent={}, started_date={}, description={}",
+                edited_item.id,
+                edited_item.ticket,
+                edited_item.time_spent,
+                edited_item.started_date,
+                edited_item.description,
+            ), |edited_item| json!({ "edited": edited_item }))
         }
-        Some(Commands::Pop {}) => {
+        Some(Commands::Pop { local }) => {
             run(
-                worklog::pop, 
+                || worklog::pop(local), 
                 |popped_item| 
-                    popped_item.map(|v| format!(
+                    popped_item.as_ref().map(|v| format!(
                         "Removed {}: ticket={}, time spent={}, description={}",
                         v.id,
                         v.ticket,
                         v.time_spent,
                         v.description,
                     ))
-                    .unwrap_or("Nothing to pop".to_string())
-            );
+                    .unwrap_or("Nothing to pop".to_string()),
+                |popped_item| json!({ "removed": popped_item })
+            )
+        }
+        Some(Commands::Commit { dry_run, strict }) => {
+            run_with_default_msg(|| worklog::commit(dry_run, strict))
+        }
+        Some(Commands::Report { from, to, group_by }) => {
+            let today = Local::now().date_naive();
+            let day = |value: Option<String>| {
+                value.map(|v| parse_date(&v)).transpose().map(|v| v.map(|v| v.date_naive()))
+            };
+
+            run_with_default_msg(|| worklog::report(
+                &day(from)?.unwrap_or(today - TimeDelta::days(today.weekday().num_days_from_monday() as i64)),
+                &day(to)?.unwrap_or(today),
+                group_by,
+            ))
         }
-        Some(Commands::Commit {}) => {
-            run_with_default_msg(worklog::commit);
+        Some(Commands::Check { day }) => {
+            run(
+                || worklog::check(
+                    &day
+                        .map(|v| parse_date(&v))
+                        .transpose()?
+                        .map_or_else(|| Local::now().date_naive(), |v| v.date_naive()),
+                ),
+                check_output,
+                |report| json!(report),
+            )
+        }
+        Some(Commands::Pull { from, to }) => {
+            let today = Local::now().date_naive();
+
+            run_with_default_msg(|| worklog::pull(
+                &from.unwrap_or(today - TimeDelta::days(7)),
+                &to.unwrap_or(today),
+            ))
         }
         Some(Commands::Current { format }) => {
             if format.is_some() {
-                run_with_default_plain(|| worklog::print_current_ticket(&format));
+                run_with_default_plain(|| worklog::print_current_ticket(&format))
             } else {
-                run_with_default_msg(|| worklog::print_current_ticket(&format));
+                run_with_default_msg(|| worklog::print_current_ticket(&format))
             }
         }
-        Some(Commands::Show { stdout }) => {
-            if stdout {
-                run_with_default_msg(worklog::worklog_to_stdout);
+        Some(Commands::Show { stdout, ticket, date }) => {
+            if stdout || output::is_json() {
+                run_with_default_msg(|| worklog::worklog_to_stdout(&ticket, &date))
             } else {
-                match run_csvlens([&worklog::worklog_path(), "--delimiter", ","]) {
-                    Ok(_) => {},
-                    Err(e) => eprintln!("Error: {:?}", e),
-                }
+                run(
+                    || {
+                        let path = worklog::worklog_csv_path()?;
+                        run_csvlens([&path, "--delimiter", ","])
+                            .map_err(|e| JiralogError::Other(format!("{:?}", e)))?;
+                        Ok::<_, JiralogError>(())
+                    },
+                    |_| String::new(),
+                    |_| Value::Null,
+                )
             }
         }
-        Some(Commands::Begin { ticket, description }) => {
-            let begin_worklog_output = |begin_worklog: BeginWorklog| {
-                match begin_worklog.previous {
+        Some(Commands::Begin { ticket, description, at }) => {
+            let begin_worklog_output = |begin_worklog: &BeginWorklog| {
+                match &begin_worklog.previous {
                     Some(previous) => 
                         format!(
-                            "End {}: ticket={}, time spent={}, description={}\n\nBegin {}: ticket={}, description={}",
-                            previous.id,
-                            previous.ticket,
-                            previous.time_spent,
-                            previous.description,
+                            "{}\n\nBegin {}: ticket={}, description={}",
+                            ended_output(previous),
                             begin_worklog.current.id,
                             begin_worklog.current.ticket,
                             begin_worklog.current.description,
@@ -158,83 +376,235 @@ fn main() {
             run(
                 || worklog::begin(
                     &ticket, 
-                    &description.unwrap_or("".to_string())
+                    &description.unwrap_or("".to_string()),
+                    &at
+                        .map(|v| parse_date(&v))
+                        .transpose()?,
                 ),
-                begin_worklog_output
-            );
+                begin_worklog_output,
+                |begin_worklog| json!(begin_worklog)
+            )
         }
-        Some(Commands::End {}) => {
-            let end_ouput = |previous: Option<WorklogRecord>| {
+        Some(Commands::End { at }) => {
+            let end_ouput = |previous: &Option<EndedWorklog>| {
                 match previous {
-                    Some(value) => 
-                        format!(
-                            "End {}: ticket={}, time spent={}, description={}",
-                            value.id,
-                            value.ticket,
-                            value.time_spent,
-                            value.description
-                        ),
+                    Some(value) => ended_output(value),
                     None => 
                         "Nothing to end".to_string()
                 }
             };
 
             run(
-                worklog::end_current,
-                end_ouput
-            );
+                || worklog::end_current(
+                    &at
+                        .map(|v| parse_date(&v))
+                        .transpose()?,
+                ),
+                end_ouput,
+                |previous| json!({ "ended": previous })
+            )
+        }
+        Some(Commands::Pause {}) => {
+            run(
+                worklog::pause,
+                |paused| match paused {
+                    Some(value) => format!(
+                        "Paused {}: ticket={}, description={}",
+                        value.id, value.ticket, value.description
+                    ),
+                    None => "Nothing to pause".to_string(),
+                },
+                |paused| json!({ "paused": paused }),
+            )
+        }
+        Some(Commands::Resume {}) => {
+            run(
+                worklog::resume,
+                |resumed| match resumed {
+                    Some(value) => format!(
+                        "Resumed {}: ticket={}, paused {}",
+                        value.id,
+                        value.ticket,
+                        TimeSpent::from_minutes((value.paused_seconds / 60) as u32)
+                    ),
+                    None => "Nothing to resume".to_string(),
+                },
+                |resumed| json!({ "resumed": resumed }),
+            )
+        }
+        Some(Commands::Configure { action, user, token, instance, url, editor, token_store, no_verify }) => {
+            match action {
+                Some(ConfigureAction::Get { property }) => {
+                    run_with_default_plain(|| config::get_property(&property))
+                }
+                Some(ConfigureAction::Set { property, value }) => {
+                    run_with_default_msg(|| config::set_property(&property, &value))
+                }
+                Some(ConfigureAction::List {}) => {
+                    run_with_default_plain(config::list_properties)
+                }
+                Some(ConfigureAction::WorkingTime {}) => {
+                    run_with_default_msg(config::fetch_working_time)
+                }
+                None if user.is_some() || token.is_some() || instance.is_some() || url.is_some() || editor.is_some() => {
+                    run_with_default_msg(|| config::configure_with(config::ConfigureOptions {
+                        user,
+                        token,
+                        instance,
+                        url,
+                        editor,
+                        token_store,
+                        verify: !no_verify,
+                    }))
+                }
+                None => {
+                    run_with_default_msg(config::configure)
+                }
+            }
         }
-        Some(Commands::Configure { }) => {
-            run_with_default_msg(worklog::configure);
+        Some(Commands::Migrate { }) => {
+            run_with_default_msg(worklog::migrate)
         }
         Some(Commands::Info { }) => {
-            run_with_default_msg(worklog::print_info);
+            run_with_default_msg(worklog::print_info)
         }
         Some(Commands::Purge { }) => {
-            run(worklog::purge, |removed_count| format!("Removed {} items", removed_count));
+            run(
+                worklog::purge,
+                |removed_count| format!("Removed {} items", removed_count),
+                |removed_count| json!({ "purged": removed_count }),
+            )
         }
-        None => {}
+        None => ExitCode::SUCCESS,
     }
 }
 
-fn run<F1, F2, T>(op: F1, output_from_ok: F2)
+fn run<F1, F2, F3, T, E>(op: F1, output_from_ok: F2, json_from_ok: F3) -> ExitCode
 where
-    F1: FnOnce() -> Result<T, Box<dyn Error>>,
-    F2: FnOnce(T) -> String,
+    F1: FnOnce() -> Result<T, E>,
+    F2: FnOnce(&T) -> String,
+    F3: FnOnce(&T) -> Value,
+    E: Into<JiralogError>,
 {
-    run_impl(op, output_from_ok, false)
+    run_impl(op, output_from_ok, json_from_ok, false)
 }
 
-fn run_impl<F1, F2, T>(op: F1, output_from_ok: F2, plain_output: bool)
+/// Print output or error, exit code by error category
+fn run_impl<F1, F2, F3, T, E>(op: F1, output_from_ok: F2, json_from_ok: F3, plain_output: bool) -> ExitCode
 where
-    F1: FnOnce() -> Result<T, Box<dyn Error>>,
-    F2: FnOnce(T) -> String,
+    F1: FnOnce() -> Result<T, E>,
+    F2: FnOnce(&T) -> String,
+    F3: FnOnce(&T) -> Value,
+    E: Into<JiralogError>,
 {
     match op() {
+        Ok(result) if output::is_json() => {
+            let document = json_from_ok(&result);
+            if !document.is_null() {
+                println!("{}", document);
+            }
+
+            ExitCode::SUCCESS
+        }
         Ok(result) => {
-            let output = output_from_ok(result);
+            let output = output_from_ok(&result);
             if !output.is_empty() && !plain_output {
                 println!("{color_bright_green}{}{color_reset}", output);
             } else if !output.is_empty() && plain_output {
                 print!("{}", output);
             }
+
+            ExitCode::SUCCESS
         }
         Err(e) => {
-            eprintln!("{color_bright_red}Error: {}{color_reset}", e);
+            let e: JiralogError = e.into();
+
+            if output::is_json() {
+                let hint = match &e {
+                    JiralogError::Config(config_error) => Some(config_error.hint()),
+                    _ => None,
+                };
+                eprintln!(
+                    "{}",
+                    json!({ "error": { "message": e.to_string(), "exit_code": e.exit_code(), "hint": hint } })
+                );
+            } else {
+                eprintln!("{color_bright_red}Error: {}{color_reset}", e);
+
+                if let JiralogError::Config(config_error) = &e {
+                    eprintln!("Hint: {}", config_error.hint());
+                }
+            }
+
+            ExitCode::from(e.exit_code())
         }
     }
 }
 
-fn run_with_default_msg<F1>(op: F1) 
+/// Date of option in local time, day start of active profile is used for dates without time
+fn parse_date(value: &str) -> Result<DateTime<FixedOffset>, JiralogError> {
+    let working_time = config::read_working_time(config::active_profile().as_deref())?;
+
+    model::get_started_date(value, working_time.day_start).map_err(JiralogError::Validation)
+}
+
+fn ended_output(ended: &EndedWorklog) -> String {
+    let item = &ended.item;
+
+    match ended.outcome {
+        EndOutcome::Kept => format!(
+            "End {}: ticket={}, time spent={}, description={}",
+            item.id, item.ticket, item.time_spent, item.description
+        ),
+        EndOutcome::Dropped => format!(
+            "Dropped {}: ticket={}, time spent={}, below minimum time spent",
+            item.id, item.ticket, item.time_spent
+        ),
+        EndOutcome::Merged => format!(
+            "Merged {} into {}: ticket={}, time spent={}",
+            item.id,
+            ended.merged_into.as_deref().unwrap_or_default(),
+            item.ticket,
+            item.time_spent
+        ),
+    }
+}
+
+fn check_output(report: &CheckReport) -> String {
+    if report.overlaps.is_empty() && report.gaps.is_empty() {
+        return format!("No overlaps or gaps on {}", report.day);
+    }
+
+    report
+        .overlaps
+        .iter()
+        .map(|v| v.to_string())
+        .chain(report.gaps.iter().map(|v| v.to_string()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Message as {"message": ...} in json, no output for empty message
+fn message_json(message: &WorklogMessage) -> Value {
+    if message.0.is_empty() {
+        Value::Null
+    } else {
+        json!({ "message": message.0 })
+    }
+}
+
+fn run_with_default_msg<F1, E>(op: F1) -> ExitCode
 where
-    F1: FnOnce() -> Result<WorklogMessage, Box<dyn Error>>,
+    F1: FnOnce() -> Result<WorklogMessage, E>,
+    E: Into<JiralogError>,
 {
-    run(op,|v| v.0)
+    run(op, |v| v.0.clone(), message_json)
 }
 
-fn run_with_default_plain<F1>(op: F1)
+fn run_with_default_plain<F1, E>(op: F1) -> ExitCode
 where
-    F1: FnOnce() -> Result<WorklogMessage, Box<dyn Error>>,
+    F1: FnOnce() -> Result<WorklogMessage, E>,
+    E: Into<JiralogError>,
 {
-    run_impl(op, |v| v.0, true)
+    run_impl(op, |v| v.0.clone(), message_json, true)
 }
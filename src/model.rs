@@ -1,8 +1,15 @@
 use nanoid::nanoid;
 
 use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
 
-use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, NaiveTime, ParseError, TimeZone};
+use chrono::{
+    Datelike, DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Weekday,
+};
+
+use crate::duration::{TimeSpent, WorkingTime};
 
 #[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
 pub struct WorklogRecord {
@@ -12,32 +19,220 @@ pub struct WorklogRecord {
     pub started_date: DateTime<FixedOffset>,
     pub committed: bool,
     pub id: String,
+    /// Worklog id in Jira, known once committed
+    #[serde(default)]
+    pub remote_id: Option<String>,
+    /// Profile of Jira instance item belongs to, none for default profile
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Time spent in whole minutes, none while item is in progress
+    #[serde(default)]
+    pub minutes: Option<u32>,
+    /// Start of ongoing pause of item in progress
+    #[serde(default)]
+    pub paused_at: Option<DateTime<FixedOffset>>,
+    /// Time item in progress was paused, not counted in time spent
+    #[serde(default)]
+    pub paused_seconds: i64,
+    /// End of last pause of item in progress, item can not end before it
+    #[serde(default)]
+    pub resumed_at: Option<DateTime<FixedOffset>>,
 }
 
 pub struct Configuration {
+    /// Profile configuration was read for, none for default profile
+    pub profile: Option<String>,
     pub token: String,
-    pub jira_cloud_instance: Option<String>,
-    pub jira_url: Option<String>,
-    pub user: String,
+    /// Jira base url, resolved from cloud instance when configured
+    pub jira_url: String,
+    pub user: Option<String>,
     pub editor: Option<String>,
+    pub api_version: ApiVersion,
+    pub auth_type: AuthType,
+    /// Timeout for whole Jira request
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    /// Retries for throttled or unavailable Jira before giving up
+    pub max_retries: u32,
+    /// Wait before first retry, doubled for each further retry
+    pub retry_backoff: Duration,
+    /// Working day and week, converts d and w units
+    pub working_time: WorkingTime,
+}
+
+/// Jira REST api version, cloud uses 3, server and data center 2
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ApiVersion {
+    V2,
+    V3,
+}
+
+/// Jira authentication, basic with user and api token or bearer with personal access token
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuthType {
+    Basic,
+    Bearer,
+}
+
+/// Where Jira token is kept, plain in properties, in OS keyring or in passphrase encrypted file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenStore {
+    Properties,
+    Keyring,
+    File,
+}
+
+/// Rounding of tracked time spent to multiple of rounding minutes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rounding {
+    None,
+    Up,
+    Down,
+    Nearest,
+}
+
+/// What end does with tracked item below minimum time spent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShortEntries {
+    /// Raise time spent to minimum
+    Keep,
+    Drop,
+    /// Add time spent to previous uncommitted item of ticket, kept when there is none
+    Merge,
 }
 
 pub struct WorklogMessage(pub String);
 
-impl Configuration {
-    pub fn get_jira_url(&self) -> String {
-        self.jira_cloud_instance
-            .as_ref()
-            .map(|instance| format!("https://{}.atlassian.net", instance))
-            .or_else(|| self.jira_url.clone())
-            .expect("Configura jira_cloud_instance or jira_url")
+/// Time spent marker for work item in progress
+pub static CURRENT_MARKER: &str = "current";
+
+impl WorklogRecord {
+    /// Set time spent in Jira format and its minutes
+    pub fn set_time_spent(&mut self, time_spent: TimeSpent) {
+        self.time_spent = time_spent.to_string();
+        self.minutes = Some(time_spent.minutes());
     }
+}
 
+impl Configuration {
     pub fn get_editor_command(&self) -> String {
         env::var("EDITOR").unwrap_or(self.editor.clone().unwrap_or("nano".to_string()))
     }
 }
 
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiVersion::V2 => write!(f, "2"),
+            ApiVersion::V3 => write!(f, "3"),
+        }
+    }
+}
+
+impl FromStr for ApiVersion {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "2" => Ok(ApiVersion::V2),
+            "3" => Ok(ApiVersion::V3),
+            _ => Err("use 2 or 3".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for AuthType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuthType::Basic => write!(f, "basic"),
+            AuthType::Bearer => write!(f, "bearer"),
+        }
+    }
+}
+
+impl FromStr for AuthType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "basic" => Ok(AuthType::Basic),
+            "bearer" => Ok(AuthType::Bearer),
+            _ => Err("use basic or bearer".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for TokenStore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenStore::Properties => write!(f, "properties"),
+            TokenStore::Keyring => write!(f, "keyring"),
+            TokenStore::File => write!(f, "file"),
+        }
+    }
+}
+
+impl FromStr for TokenStore {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "properties" => Ok(TokenStore::Properties),
+            "keyring" => Ok(TokenStore::Keyring),
+            "file" => Ok(TokenStore::File),
+            _ => Err("use properties, keyring or file".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Rounding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rounding::None => write!(f, "none"),
+            Rounding::Up => write!(f, "up"),
+            Rounding::Down => write!(f, "down"),
+            Rounding::Nearest => write!(f, "nearest"),
+        }
+    }
+}
+
+impl FromStr for Rounding {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "none" => Ok(Rounding::None),
+            "up" => Ok(Rounding::Up),
+            "down" => Ok(Rounding::Down),
+            "nearest" => Ok(Rounding::Nearest),
+            _ => Err("use none, up, down or nearest".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ShortEntries {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShortEntries::Keep => write!(f, "keep"),
+            ShortEntries::Drop => write!(f, "drop"),
+            ShortEntries::Merge => write!(f, "merge"),
+        }
+    }
+}
+
+impl FromStr for ShortEntries {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "keep" => Ok(ShortEntries::Keep),
+            "drop" => Ok(ShortEntries::Drop),
+            "merge" => Ok(ShortEntries::Merge),
+            _ => Err("use keep, drop or merge".to_string()),
+        }
+    }
+}
+
 static NANO_ID_ALPHABET: [char; 16] = [
     '1', '2', '3', '4', '5', '6', '7', '8', '9', '0', 'a', 'b', 'c', 'd', 'e', 'f',
 ];
@@ -45,24 +240,112 @@ pub fn get_nano_id() -> String {
     nanoid!(10, &NANO_ID_ALPHABET)
 }
 
-pub fn get_started_date(started_date: &str) -> Result<DateTime<FixedOffset>, String> {
-    let date_time = NaiveDateTime::parse_from_str(started_date, "%Y-%m-%dT%H:%M")
-        .or_else(|_| date_time_from_time(started_date));
+static DATE_FORMATS: &str = "use 'YYYY-MM-DDTHH:MM', 'YYYY-MM-DD HH:MM:SS', 'H:M', 'H:M:S', \
+'2024-12-07', 'yesterday 9:00', 'mon 14:00', 'last friday', '-3d 10:00', '-20m', '15 minutes ago' \
+or 'now', date without time is start of working day";
 
-    match date_time {
-        Ok(value) => Local::now()
-            .offset()
-            .from_local_datetime(&value)
-            .single()
-            .ok_or_else(|| "Ambiguous or invalid datetime".to_string()),
-        Err(_) => Err("Invalid started date, use 'YYYY-MM-DDTHH:MM' or 'H:M'.".to_string()),
+/// Date and time in local time. Accepts dates like 2024-12-07, 'yesterday', 'mon',
+/// 'last friday' or '-3d' with optional time H:M or H:M:S, day start is used without time.
+/// Relative times like '-20m' or '15 minutes ago' are calendar time, day is 24h.
+pub fn get_started_date(started_date: &str, day_start: NaiveTime) -> Result<DateTime<FixedOffset>, String> {
+    let input = started_date.trim();
+    let invalid = || format!("Invalid date {}, {}.", input, DATE_FORMATS);
+    let now = Local::now().fixed_offset();
+    let today = now.date_naive();
+    let ago = |duration: &str| {
+        let calendar = WorkingTime {
+            hours_per_day: 24.0,
+            days_per_week: 7.0,
+            day_start,
+        };
+
+        TimeSpent::parse(duration, &calendar)
+            .map(|v| now - TimeDelta::minutes(v.minutes() as i64))
+            .map_err(|_| invalid())
+    };
+
+    if input.eq_ignore_ascii_case("now") {
+        return Ok(now);
     }
+    if let Some(duration) = input.strip_suffix("ago") {
+        return ago(duration);
+    }
+
+    let date_time = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok());
+
+    let date_time = match date_time {
+        Some(value) => value,
+        None => {
+            let (day, time) = match input.rsplit_once(char::is_whitespace) {
+                Some((day, time)) if parse_time(time).is_some() => (day.trim(), parse_time(time)),
+                _ => match parse_time(input) {
+                    Some(time) => ("", Some(time)),
+                    None => (input, None),
+                },
+            };
+            let day = day.to_lowercase();
+
+            let date = if day.is_empty() || day == "today" {
+                today
+            } else if day == "yesterday" {
+                today - TimeDelta::days(1)
+            } else if let Ok(date) = NaiveDate::parse_from_str(&day, "%Y-%m-%d") {
+                date
+            } else if let Some(weekday) = day.strip_prefix("last ") {
+                let days = days_since(today, weekday.trim()).ok_or_else(invalid)?;
+                today - TimeDelta::days(if days == 0 { 7 } else { days })
+            } else if let Some(days) = days_since(today, &day) {
+                today - TimeDelta::days(days)
+            } else if let Some(offset) = day.strip_prefix('-') {
+                match offset.strip_suffix('d').map(|v| v.trim().parse::<i64>()) {
+                    Some(Ok(days)) => today - TimeDelta::days(days),
+                    _ if time.is_none() => return ago(offset),
+                    _ => return Err(invalid()),
+                }
+            } else {
+                return Err(invalid());
+            };
+
+            date.and_time(time.unwrap_or(day_start))
+        }
+    };
+
+    local_date_time(&date_time)
 }
 
-fn date_time_from_time(started_date: &str) -> Result<NaiveDateTime, ParseError> {
-    let naive_time = NaiveTime::parse_from_str(started_date, "%H:%M")?;
-    let today = Local::now().naive_local().date();
+/// Midnight of given date in local time
+pub fn start_of_day(date: &NaiveDate) -> Result<DateTime<FixedOffset>, String> {
+    local_date_time(&date.and_time(NaiveTime::MIN))
+}
+
+/// Midnight after given date in local time, end of day exclusive
+pub fn end_of_day(date: &NaiveDate) -> Result<DateTime<FixedOffset>, String> {
+    let next_day = date.succ_opt().ok_or_else(|| format!("No day after {}", date))?;
+
+    start_of_day(&next_day)
+}
 
-    Ok(NaiveDateTime::new(today, naive_time))
+/// Local time with offset in effect at that time, earlier one when clocks are turned back
+fn local_date_time(date_time: &NaiveDateTime) -> Result<DateTime<FixedOffset>, String> {
+    Local
+        .from_local_datetime(date_time)
+        .earliest()
+        .map(|v| v.fixed_offset())
+        .ok_or_else(|| format!("Invalid local time {}, skipped by daylight saving time", date_time))
 }
 
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+        .ok()
+}
+
+/// Days from latest given weekday to date, 0 when date is on the weekday
+fn days_since(date: NaiveDate, weekday: &str) -> Option<i64> {
+    let weekday = Weekday::from_str(weekday).ok()?;
+    let days = date.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday();
+
+    Some((days % 7) as i64)
+}
@@ -0,0 +1,29 @@
+use serde::Serialize;
+use std::sync::OnceLock;
+
+use crate::error::JiralogError;
+
+static OUTPUT_FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+/// Format of command output, prose for humans or JSON for scripts
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// Use given output format for all command output. Call before running command.
+pub fn set_output_format(format: OutputFormat) {
+    let _ = OUTPUT_FORMAT.set(format);
+}
+
+pub fn is_json() -> bool {
+    OUTPUT_FORMAT.get() == Some(&OutputFormat::Json)
+}
+
+/// Print value as single line JSON document to stdout
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<(), JiralogError> {
+    println!("{}", serde_json::to_string(value)?);
+
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+pub static CONFIG_FILE: &str = "jiralog.properties";
+static TOKEN_FILE: &str = "token.enc";
+static WORKLOG_FILE: &str = "worklog.csv";
+static WORKLOG_DB_FILE: &str = "worklog.db";
+static WORKLOG_EXPORT_FILE: &str = "worklog_export.csv";
+static MIGRATED_WORKLOG_FILE: &str = "worklog.csv.migrated";
+static WORKLOG_LOCK_FILE: &str = "worklog.lock";
+static COMMIT_FILE: &str = "commit_worklog";
+
+static LEGACY_DIR: &str = ".jiralog";
+static APP_DIR: &str = "jiralog";
+
+static HOME_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+static DIRS: OnceLock<Result<JiralogDirs, String>> = OnceLock::new();
+
+/// Directories for configuration and worklog data, same directory when home is given
+struct JiralogDirs {
+    config: PathBuf,
+    data: PathBuf,
+}
+
+/// Use given directory as jiralog home, wins over JIRALOG_HOME. Call before resolving any path.
+pub fn set_home(home: PathBuf) {
+    let _ = HOME_OVERRIDE.set(home);
+}
+
+/// Home from --home or JIRALOG_HOME, otherwise XDG config and data directories.
+/// Legacy $home/.jiralog is moved to XDG directories on first use.
+fn jiralog_dirs() -> Result<&'static JiralogDirs, Box<dyn Error>> {
+    DIRS.get_or_init(resolve_dirs)
+        .as_ref()
+        .map_err(|err| err.clone().into())
+}
+
+fn resolve_dirs() -> Result<JiralogDirs, String> {
+    let home = HOME_OVERRIDE
+        .get()
+        .cloned()
+        .or_else(|| env::var_os("JIRALOG_HOME").map(PathBuf::from));
+
+    if let Some(home) = home {
+        return Ok(JiralogDirs {
+            config: home.clone(),
+            data: home,
+        });
+    }
+
+    let not_found = "Could not locate home directory, set JIRALOG_HOME or use --home";
+    let jiralog_dirs = JiralogDirs {
+        config: dirs::config_dir().ok_or(not_found)?.join(APP_DIR),
+        data: dirs::data_dir().ok_or(not_found)?.join(APP_DIR),
+    };
+
+    if let Some(legacy_dir) = dirs::home_dir().map(|home| home.join(LEGACY_DIR)) {
+        if legacy_dir.is_dir() && !is_initialized(&jiralog_dirs) {
+            migrate_legacy_dir(&legacy_dir, &jiralog_dirs)
+                .map_err(|err| format!("Moving {} failed: {}", legacy_dir.display(), err))?;
+        }
+    }
+
+    Ok(jiralog_dirs)
+}
+
+fn is_initialized(jiralog_dirs: &JiralogDirs) -> bool {
+    jiralog_dirs.config.join(CONFIG_FILE).exists()
+        || jiralog_dirs.data.join(WORKLOG_FILE).exists()
+        || jiralog_dirs.data.join(WORKLOG_DB_FILE).exists()
+}
+
+/// Properties and encrypted tokens go to config directory, everything else to data directory
+fn migrate_legacy_dir(legacy_dir: &Path, jiralog_dirs: &JiralogDirs) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(&jiralog_dirs.config)?;
+    fs::create_dir_all(&jiralog_dirs.data)?;
+
+    for entry in fs::read_dir(legacy_dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+
+        let name = file_name.to_string_lossy();
+        let target_dir = if name.ends_with(".properties") || name.ends_with(".enc") {
+            &jiralog_dirs.config
+        } else {
+            &jiralog_dirs.data
+        };
+
+        move_file(&entry.path(), &target_dir.join(&file_name))?;
+    }
+
+    let _ = fs::remove_dir(legacy_dir);
+
+    eprintln!(
+        "Moved {} to {} and {}",
+        legacy_dir.display(),
+        jiralog_dirs.config.display(),
+        jiralog_dirs.data.display()
+    );
+
+    Ok(())
+}
+
+/// Rename, or copy and remove when rename is not possible across file systems
+fn move_file(from: &Path, to: &Path) -> Result<(), Box<dyn Error>> {
+    if fs::rename(from, to).is_err() {
+        fs::copy(from, to)?;
+        fs::remove_file(from)?;
+    }
+
+    Ok(())
+}
+
+pub fn get_config_dir_path() -> Result<PathBuf, Box<dyn Error>> {
+    Ok(jiralog_dirs()?.config.clone())
+}
+
+pub fn get_data_dir_path() -> Result<PathBuf, Box<dyn Error>> {
+    Ok(jiralog_dirs()?.data.clone())
+}
+
+/// Configuration of profile, jiralog.properties for default profile
+pub fn get_config_path(profile: Option<&str>) -> Result<PathBuf, Box<dyn Error>> {
+    let file_name = match profile {
+        Some(profile) => format!("jiralog-{}.properties", profile),
+        None => CONFIG_FILE.to_string(),
+    };
+
+    Ok(get_config_dir_path()?.join(file_name))
+}
+
+/// Encrypted token of profile, token.enc for default profile
+pub fn get_token_path(profile: Option<&str>) -> Result<PathBuf, Box<dyn Error>> {
+    let file_name = match profile {
+        Some(profile) => format!("token-{}.enc", profile),
+        None => TOKEN_FILE.to_string(),
+    };
+
+    Ok(get_config_dir_path()?.join(file_name))
+}
+
+pub fn get_worklog_path() -> Result<PathBuf, Box<dyn Error>> {
+    Ok(get_data_dir_path()?.join(WORKLOG_FILE))
+}
+
+pub fn get_worklog_db_path() -> Result<PathBuf, Box<dyn Error>> {
+    Ok(get_data_dir_path()?.join(WORKLOG_DB_FILE))
+}
+
+pub fn get_worklog_export_path() -> Result<PathBuf, Box<dyn Error>> {
+    Ok(get_data_dir_path()?.join(WORKLOG_EXPORT_FILE))
+}
+
+pub fn get_migrated_worklog_path() -> Result<PathBuf, Box<dyn Error>> {
+    Ok(get_data_dir_path()?.join(MIGRATED_WORKLOG_FILE))
+}
+
+pub fn get_lock_path() -> Result<PathBuf, Box<dyn Error>> {
+    Ok(get_data_dir_path()?.join(WORKLOG_LOCK_FILE))
+}
+
+pub fn get_commit_path() -> Result<PathBuf, Box<dyn Error>> {
+    Ok(get_data_dir_path()?.join(COMMIT_FILE))
+}
@@ -0,0 +1,133 @@
+use chrono::{Datelike, NaiveDate, TimeDelta};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::model::WorklogRecord;
+
+/// Grouping of report rows
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    Day,
+    Week,
+    Ticket,
+    /// Project key of ticket, ABC of ABC-1
+    Project,
+}
+
+/// Totals of time spent between days
+#[derive(Debug, serde::Serialize)]
+pub struct Report {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub group_by: GroupBy,
+    pub daily_target_minutes: u32,
+    pub rows: Vec<ReportRow>,
+    pub committed_minutes: u32,
+    pub uncommitted_minutes: u32,
+    pub total_minutes: u32,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct ReportRow {
+    pub key: String,
+    pub committed_minutes: u32,
+    pub uncommitted_minutes: u32,
+    pub total_minutes: u32,
+    /// Target of day or working days of week within report, none for tickets and projects
+    pub target_minutes: Option<u32>,
+    pub under_target: bool,
+}
+
+impl Report {
+    /// Report of items with their minutes. Days and weeks include working days without items,
+    /// first days of week are working days.
+    pub fn new(
+        items: &[(&WorklogRecord, u32)],
+        from: &NaiveDate,
+        to: &NaiveDate,
+        group_by: GroupBy,
+        daily_target_minutes: u32,
+        working_days: u32,
+    ) -> Self {
+        let mut rows: BTreeMap<String, ReportRow> = BTreeMap::new();
+        let is_working_day = |date: &NaiveDate| date.weekday().num_days_from_monday() < working_days;
+
+        if matches!(group_by, GroupBy::Day | GroupBy::Week) {
+            let mut date = *from;
+            while date <= *to {
+                if is_working_day(&date) {
+                    let row = rows
+                        .entry(group_key(group_by, &date, ""))
+                        .or_insert_with_key(|key| ReportRow::new(key, Some(0)));
+                    row.target_minutes = row.target_minutes.map(|v| v + daily_target_minutes);
+                }
+                date += TimeDelta::days(1);
+            }
+        }
+
+        for (item, minutes) in items {
+            let key = group_key(group_by, &item.started_date.date_naive(), &item.ticket);
+            let row = rows
+                .entry(key)
+                .or_insert_with_key(|key| ReportRow::new(key, None));
+
+            if item.committed {
+                row.committed_minutes += minutes;
+            } else {
+                row.uncommitted_minutes += minutes;
+            }
+            row.total_minutes += minutes;
+        }
+
+        let mut rows: Vec<ReportRow> = rows.into_values().collect();
+        for row in rows.iter_mut() {
+            row.under_target = row.target_minutes.is_some_and(|v| row.total_minutes < v);
+        }
+
+        Report {
+            from: *from,
+            to: *to,
+            group_by,
+            daily_target_minutes,
+            committed_minutes: rows.iter().map(|v| v.committed_minutes).sum(),
+            uncommitted_minutes: rows.iter().map(|v| v.uncommitted_minutes).sum(),
+            total_minutes: rows.iter().map(|v| v.total_minutes).sum(),
+            rows,
+        }
+    }
+}
+
+impl ReportRow {
+    fn new(key: &str, target_minutes: Option<u32>) -> Self {
+        ReportRow {
+            key: key.to_string(),
+            committed_minutes: 0,
+            uncommitted_minutes: 0,
+            total_minutes: 0,
+            target_minutes,
+            under_target: false,
+        }
+    }
+}
+
+/// Keys sort in order of days and weeks, ISO week like 2024-W49
+fn group_key(group_by: GroupBy, date: &NaiveDate, ticket: &str) -> String {
+    match group_by {
+        GroupBy::Day => date.format("%Y-%m-%d").to_string(),
+        GroupBy::Week => date.format("%G-W%V").to_string(),
+        GroupBy::Ticket => ticket.to_string(),
+        GroupBy::Project => ticket.split_once('-').map_or(ticket, |(project, _)| project).to_string(),
+    }
+}
+
+impl fmt::Display for GroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GroupBy::Day => write!(f, "day"),
+            GroupBy::Week => write!(f, "week"),
+            GroupBy::Ticket => write!(f, "ticket"),
+            GroupBy::Project => write!(f, "project"),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+mod csv_store;
+mod sqlite_store;
+
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, FixedOffset};
+use tempfile::NamedTempFile;
+
+use crate::model::{WorklogRecord, CURRENT_MARKER};
+
+pub use csv_store::CsvStore;
+pub use sqlite_store::SqliteStore;
+
+/// Persistence for worklog records. Default lookups scan `read_all`, stores with
+/// indexes should override them.
+pub trait WorklogStore {
+    /// All records in insertion order
+    fn read_all(&self) -> Result<Vec<WorklogRecord>, Box<dyn Error>>;
+
+    fn insert(&self, item: &WorklogRecord) -> Result<(), Box<dyn Error>>;
+
+    fn insert_all(&self, items: &[WorklogRecord]) -> Result<(), Box<dyn Error>> {
+        items.iter().try_for_each(|item| self.insert(item))
+    }
+
+    /// Replace record with same id, returns false if no such record
+    fn update(&self, item: &WorklogRecord) -> Result<bool, Box<dyn Error>>;
+
+    fn remove(&self, id: &str) -> Result<Option<WorklogRecord>, Box<dyn Error>>;
+
+    /// Remove committed records, returns removed count
+    fn purge_committed(&self) -> Result<usize, Box<dyn Error>>;
+
+    /// Path to worklog in csv format, for tools reading csv directly
+    fn export_csv(&self) -> Result<PathBuf, Box<dyn Error>>;
+
+    fn read_uncommitted(&self) -> Result<Vec<WorklogRecord>, Box<dyn Error>> {
+        Ok(self.read_all()?.into_iter().filter(|v| !v.committed).collect())
+    }
+
+    fn find(&self, id: &str) -> Result<Option<WorklogRecord>, Box<dyn Error>> {
+        Ok(self.read_all()?.into_iter().find(|v| v.id == id))
+    }
+
+    /// Item with Jira worklog id, ids are unique within one Jira instance only
+    fn find_by_remote_id(
+        &self,
+        remote_id: &str,
+        profile: Option<&str>,
+    ) -> Result<Option<WorklogRecord>, Box<dyn Error>> {
+        Ok(self.read_all()?.into_iter().find(|v| {
+            v.remote_id.as_deref() == Some(remote_id) && v.profile.as_deref() == profile
+        }))
+    }
+
+    fn find_by_ticket(&self, ticket: &str) -> Result<Vec<WorklogRecord>, Box<dyn Error>> {
+        Ok(self.read_all()?.into_iter().filter(|v| v.ticket == ticket).collect())
+    }
+
+    /// Records started within [from, to)
+    fn find_by_started_date(
+        &self,
+        from: &DateTime<FixedOffset>,
+        to: &DateTime<FixedOffset>,
+    ) -> Result<Vec<WorklogRecord>, Box<dyn Error>> {
+        Ok(self
+            .read_all()?
+            .into_iter()
+            .filter(|v| &v.started_date >= from && &v.started_date < to)
+            .collect())
+    }
+
+    /// Latest inserted record
+    fn last(&self) -> Result<Option<WorklogRecord>, Box<dyn Error>> {
+        Ok(self.read_all()?.pop())
+    }
+
+    fn current(&self) -> Result<Option<WorklogRecord>, Box<dyn Error>> {
+        Ok(self.read_all()?.into_iter().find(|v| v.time_spent == CURRENT_MARKER))
+    }
+}
+
+/// Advisory lock on the worklog shared between jiralog processes, released on drop
+pub struct WorklogLock {
+    _file: File,
+}
+
+impl WorklogLock {
+    /// Lock for read-modify-write, blocks until other holders are done
+    pub fn exclusive(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let file = open_lock_file(path)?;
+        file.lock()?;
+
+        Ok(WorklogLock { _file: file })
+    }
+
+    /// Lock for reading, allows other readers
+    pub fn shared(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let file = open_lock_file(path)?;
+        file.lock_shared()?;
+
+        Ok(WorklogLock { _file: file })
+    }
+}
+
+fn open_lock_file(path: &Path) -> Result<File, Box<dyn Error>> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+
+    Ok(file)
+}
+
+/// Write records to uniquely named temporary file next to path and rename over it,
+/// readers never see partial file and concurrent writers never share a temporary file
+fn write_csv_atomic(path: &Path, records: &[WorklogRecord]) -> Result<(), Box<dyn Error>> {
+    let dir = path.parent().filter(|v| !v.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let temp_file = NamedTempFile::new_in(dir)?;
+
+    let mut writer = csv::WriterBuilder::new().from_writer(temp_file.as_file());
+    records.iter().try_for_each(|v| writer.serialize(v))?;
+    writer.flush()?;
+    drop(writer);
+
+    temp_file.as_file().sync_all()?;
+    temp_file.persist(path)?;
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::path::PathBuf;
+
+use crate::model::WorklogRecord;
+
+use super::{write_csv_atomic, WorklogStore};
+
+/// Columns of worklog written by this version, files with other columns are rewritten on insert
+static COLUMNS: [&str; 12] = [
+    "ticket",
+    "time_spent",
+    "description",
+    "started_date",
+    "committed",
+    "id",
+    "remote_id",
+    "profile",
+    "minutes",
+    "paused_at",
+    "paused_seconds",
+    "resumed_at",
+];
+
+/// Worklog kept in a single csv file, inserts append rows and other modifications atomically
+/// replace the file
+pub struct CsvStore {
+    path: PathBuf,
+}
+
+impl CsvStore {
+    pub fn new(path: PathBuf) -> Self {
+        CsvStore { path }
+    }
+
+    fn write_all(&self, worklog: &[WorklogRecord]) -> Result<(), Box<dyn Error>> {
+        write_csv_atomic(&self.path, worklog)
+    }
+
+    /// Header of worklog file, none when file is missing or empty
+    fn read_header(&self) -> Result<Option<csv::StringRecord>, Box<dyn Error>> {
+        let Ok(file) = File::open(&self.path) else {
+            return Ok(None);
+        };
+        let header = csv::Reader::from_reader(file).headers()?.clone();
+
+        Ok(Some(header).filter(|v| !v.is_empty()))
+    }
+}
+
+impl WorklogStore for CsvStore {
+    fn read_all(&self) -> Result<Vec<WorklogRecord>, Box<dyn Error>> {
+        if let Ok(file) = File::open(&self.path) {
+            let mut rdr = csv::Reader::from_reader(file);
+
+            let worklog_records: Vec<WorklogRecord> = rdr.deserialize().collect::<Result<_, _>>()?;
+
+            Ok(worklog_records)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    fn insert(&self, item: &WorklogRecord) -> Result<(), Box<dyn Error>> {
+        self.insert_all(std::slice::from_ref(item))
+    }
+
+    fn insert_all(&self, items: &[WorklogRecord]) -> Result<(), Box<dyn Error>> {
+        let header = self.read_header()?;
+
+        // Rows of older worklog have fewer columns, rewrite it with current columns
+        if header.as_ref().is_some_and(|v| v != COLUMNS[..]) {
+            let mut worklog = self.read_all()?;
+            worklog.extend_from_slice(items);
+
+            return self.write_all(&worklog);
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(header.is_none())
+            .from_writer(&mut file);
+        items.iter().try_for_each(|v| writer.serialize(v))?;
+        writer.flush()?;
+        drop(writer);
+
+        file.sync_all()?;
+
+        Ok(())
+    }
+
+    fn update(&self, item: &WorklogRecord) -> Result<bool, Box<dyn Error>> {
+        let mut worklog = self.read_all()?;
+
+        if let Some(index) = worklog.iter().position(|r| r.id == item.id) {
+            worklog[index] = item.clone();
+            self.write_all(&worklog)?;
+
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn remove(&self, id: &str) -> Result<Option<WorklogRecord>, Box<dyn Error>> {
+        let mut worklog = self.read_all()?;
+
+        if let Some(item_position) = worklog.iter().position(|v| v.id == id) {
+            let removed = worklog.remove(item_position);
+            self.write_all(&worklog)?;
+
+            Ok(Some(removed))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn purge_committed(&self) -> Result<usize, Box<dyn Error>> {
+        let worklog = self.read_all()?;
+        let worklog_length = worklog.len();
+
+        let uncommitted: Vec<WorklogRecord> = worklog.into_iter().filter(|v| !v.committed).collect();
+        self.write_all(&uncommitted)?;
+
+        Ok(worklog_length - uncommitted.len())
+    }
+
+    fn export_csv(&self) -> Result<PathBuf, Box<dyn Error>> {
+        Ok(self.path.clone())
+    }
+}
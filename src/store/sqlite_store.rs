@@ -0,0 +1,260 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use chrono::{DateTime, FixedOffset};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::model::{WorklogRecord, CURRENT_MARKER};
+
+use super::{write_csv_atomic, WorklogStore};
+
+/// Schema migrations, index + 1 is stored as user_version once applied
+static MIGRATIONS: [&str; 6] = ["
+    CREATE TABLE worklog (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        id TEXT NOT NULL UNIQUE,
+        ticket TEXT NOT NULL,
+        time_spent TEXT NOT NULL,
+        description TEXT NOT NULL,
+        started_date TEXT NOT NULL,
+        started_ts INTEGER NOT NULL,
+        committed INTEGER NOT NULL
+    );
+    CREATE INDEX worklog_ticket ON worklog(ticket);
+    CREATE INDEX worklog_started_ts ON worklog(started_ts);
+    CREATE INDEX worklog_committed ON worklog(committed);
+", "
+    ALTER TABLE worklog ADD COLUMN remote_id TEXT;
+    CREATE INDEX worklog_remote_id ON worklog(remote_id);
+", "
+    ALTER TABLE worklog ADD COLUMN profile TEXT;
+", "
+    ALTER TABLE worklog ADD COLUMN minutes INTEGER;
+", "
+    ALTER TABLE worklog ADD COLUMN paused_at TEXT;
+    ALTER TABLE worklog ADD COLUMN paused_seconds INTEGER NOT NULL DEFAULT 0;
+", "
+    ALTER TABLE worklog ADD COLUMN resumed_at TEXT;
+"];
+
+static COLUMNS: &str = "ticket, time_spent, description, started_date, committed, id, remote_id, profile, minutes, paused_at, paused_seconds, resumed_at";
+
+/// Worklog kept in sqlite database, records indexed by id, ticket and started date
+pub struct SqliteStore {
+    connection: Connection,
+    export_path: PathBuf,
+}
+
+impl SqliteStore {
+    pub fn open(path: PathBuf, export_path: PathBuf) -> Result<Self, Box<dyn Error>> {
+        let connection = Connection::open(path)?;
+        migrate(&connection)?;
+
+        Ok(SqliteStore {
+            connection,
+            export_path,
+        })
+    }
+
+    fn query(
+        &self,
+        condition: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<WorklogRecord>, Box<dyn Error>> {
+        let sql = format!("SELECT {} FROM worklog {} ORDER BY seq", COLUMNS, condition);
+        let mut statement = self.connection.prepare(&sql)?;
+
+        let records = statement
+            .query_map(params, from_row)?
+            .collect::<Result<_, _>>()?;
+
+        Ok(records)
+    }
+
+    fn query_one(
+        &self,
+        condition: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Option<WorklogRecord>, Box<dyn Error>> {
+        let sql = format!("SELECT {} FROM worklog {}", COLUMNS, condition);
+
+        let record = self
+            .connection
+            .query_row(&sql, params, from_row)
+            .optional()?;
+
+        Ok(record)
+    }
+}
+
+impl WorklogStore for SqliteStore {
+    fn read_all(&self) -> Result<Vec<WorklogRecord>, Box<dyn Error>> {
+        self.query("", [])
+    }
+
+    fn insert(&self, item: &WorklogRecord) -> Result<(), Box<dyn Error>> {
+        insert_record(&self.connection, item)
+    }
+
+    fn insert_all(&self, items: &[WorklogRecord]) -> Result<(), Box<dyn Error>> {
+        let transaction = self.connection.unchecked_transaction()?;
+        items
+            .iter()
+            .try_for_each(|item| insert_record(&transaction, item))?;
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    fn update(&self, item: &WorklogRecord) -> Result<bool, Box<dyn Error>> {
+        let updated = self.connection.execute(
+            "UPDATE worklog SET ticket = ?1, time_spent = ?2, description = ?3, started_date = ?4, started_ts = ?5, committed = ?6, remote_id = ?7, profile = ?8, minutes = ?9, paused_at = ?10, paused_seconds = ?11, resumed_at = ?12 WHERE id = ?13",
+            params![
+                item.ticket,
+                item.time_spent,
+                item.description,
+                item.started_date.to_rfc3339(),
+                item.started_date.timestamp(),
+                item.committed,
+                item.remote_id,
+                item.profile,
+                item.minutes,
+                item.paused_at.map(|v| v.to_rfc3339()),
+                item.paused_seconds,
+                item.resumed_at.map(|v| v.to_rfc3339()),
+                item.id,
+            ],
+        )?;
+
+        Ok(updated > 0)
+    }
+
+    fn remove(&self, id: &str) -> Result<Option<WorklogRecord>, Box<dyn Error>> {
+        let item = self.find(id)?;
+
+        if item.is_some() {
+            self.connection
+                .execute("DELETE FROM worklog WHERE id = ?1", [id])?;
+        }
+
+        Ok(item)
+    }
+
+    fn purge_committed(&self) -> Result<usize, Box<dyn Error>> {
+        let removed = self
+            .connection
+            .execute("DELETE FROM worklog WHERE committed = 1", [])?;
+
+        Ok(removed)
+    }
+
+    fn export_csv(&self) -> Result<PathBuf, Box<dyn Error>> {
+        write_csv_atomic(&self.export_path, &self.read_all()?)?;
+
+        Ok(self.export_path.clone())
+    }
+
+    fn read_uncommitted(&self) -> Result<Vec<WorklogRecord>, Box<dyn Error>> {
+        self.query("WHERE committed = 0", [])
+    }
+
+    fn find(&self, id: &str) -> Result<Option<WorklogRecord>, Box<dyn Error>> {
+        self.query_one("WHERE id = ?1", [id])
+    }
+
+    fn find_by_remote_id(
+        &self,
+        remote_id: &str,
+        profile: Option<&str>,
+    ) -> Result<Option<WorklogRecord>, Box<dyn Error>> {
+        self.query_one(
+            "WHERE remote_id = ?1 AND profile IS ?2",
+            params![remote_id, profile],
+        )
+    }
+
+    fn find_by_ticket(&self, ticket: &str) -> Result<Vec<WorklogRecord>, Box<dyn Error>> {
+        self.query("WHERE ticket = ?1", [ticket])
+    }
+
+    fn find_by_started_date(
+        &self,
+        from: &DateTime<FixedOffset>,
+        to: &DateTime<FixedOffset>,
+    ) -> Result<Vec<WorklogRecord>, Box<dyn Error>> {
+        self.query(
+            "WHERE started_ts >= ?1 AND started_ts < ?2",
+            [from.timestamp(), to.timestamp()],
+        )
+    }
+
+    fn last(&self) -> Result<Option<WorklogRecord>, Box<dyn Error>> {
+        self.query_one("ORDER BY seq DESC LIMIT 1", [])
+    }
+
+    fn current(&self) -> Result<Option<WorklogRecord>, Box<dyn Error>> {
+        self.query_one("WHERE time_spent = ?1 LIMIT 1", [CURRENT_MARKER])
+    }
+}
+
+fn migrate(connection: &Connection) -> Result<(), Box<dyn Error>> {
+    let version: usize = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let transaction = connection.unchecked_transaction()?;
+        transaction.execute_batch(migration)?;
+        transaction.pragma_update(None, "user_version", index + 1)?;
+        transaction.commit()?;
+    }
+
+    Ok(())
+}
+
+fn insert_record(connection: &Connection, item: &WorklogRecord) -> Result<(), Box<dyn Error>> {
+    connection.execute(
+        "INSERT INTO worklog (ticket, time_spent, description, started_date, started_ts, committed, id, remote_id, profile, minutes, paused_at, paused_seconds, resumed_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            item.ticket,
+            item.time_spent,
+            item.description,
+            item.started_date.to_rfc3339(),
+            item.started_date.timestamp(),
+            item.committed,
+            item.id,
+            item.remote_id,
+            item.profile,
+            item.minutes,
+            item.paused_at.map(|v| v.to_rfc3339()),
+            item.paused_seconds,
+            item.resumed_at.map(|v| v.to_rfc3339()),
+        ],
+    )?;
+
+    Ok(())
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<WorklogRecord> {
+    let date = |index: usize, value: &str| {
+        DateTime::parse_from_rfc3339(value).map_err(|err| {
+            rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(err))
+        })
+    };
+    let started_date: String = row.get(3)?;
+    let paused_at: Option<String> = row.get(9)?;
+    let resumed_at: Option<String> = row.get(11)?;
+
+    Ok(WorklogRecord {
+        ticket: row.get(0)?,
+        time_spent: row.get(1)?,
+        description: row.get(2)?,
+        started_date: date(3, &started_date)?,
+        committed: row.get(4)?,
+        id: row.get(5)?,
+        remote_id: row.get(6)?,
+        profile: row.get(7)?,
+        minutes: row.get(8)?,
+        paused_at: paused_at.map(|v| date(9, &v)).transpose()?,
+        paused_seconds: row.get(10)?,
+        resumed_at: resumed_at.map(|v| date(11, &v)).transpose()?,
+    })
+}
@@ -0,0 +1,154 @@
+use argon2::Argon2;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io::{stdin, stdout, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::config::profile_name;
+use crate::paths::get_token_path;
+
+static KEYRING_SERVICE: &str = "jiralog";
+static PASSPHRASE_ENV: &str = "JIRALOG_PASSPHRASE";
+static SALT_LEN: usize = 16;
+static NONCE_LEN: usize = 12;
+
+/// Token from stdout of shell command, for example `pass show jira`
+pub fn run_token_command(command: &str) -> Result<String, Box<dyn Error>> {
+    let output = if cfg!(windows) {
+        Command::new("cmd").arg("/C").arg(command).output()?
+    } else {
+        Command::new("sh").arg("-c").arg(command).output()?
+    };
+
+    if !output.status.success() {
+        return Err(format!(
+            "token_command exited with {}: {}",
+            output.status.code().unwrap_or(1),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    let token = String::from_utf8(output.stdout)?.trim().to_string();
+
+    if token.is_empty() {
+        return Err("token_command printed no token".into());
+    }
+
+    Ok(token)
+}
+
+/// Token from OS keyring, Secret Service on Linux, Keychain on macOS, Credential Manager on Windows
+pub fn read_keyring(profile: Option<&str>) -> Result<String, Box<dyn Error>> {
+    keyring_entry(profile)?
+        .get_password()
+        .map_err(|err| format!("Reading token from keyring failed: {}", err).into())
+}
+
+pub fn write_keyring(profile: Option<&str>, token: &str) -> Result<(), Box<dyn Error>> {
+    keyring_entry(profile)?.set_password(token)?;
+
+    Ok(())
+}
+
+fn keyring_entry(profile: Option<&str>) -> Result<keyring::Entry, Box<dyn Error>> {
+    Ok(keyring::Entry::new(KEYRING_SERVICE, profile_name(profile))?)
+}
+
+/// Token from file encrypted with passphrase, for systems without keyring
+pub fn read_encrypted(profile: Option<&str>) -> Result<String, Box<dyn Error>> {
+    let path = get_token_path(profile)?;
+    let content = fs::read_to_string(&path)
+        .map_err(|err| format!("Reading {} failed: {}", path.display(), err))?;
+    let content = BASE64_STANDARD.decode(content.trim())?;
+
+    if content.len() < SALT_LEN + NONCE_LEN {
+        return Err(format!("Invalid token file {}", path.display()).into());
+    }
+
+    let (salt, rest) = content.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(&passphrase(false)?, salt)?);
+    let token = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Decrypting token failed, check passphrase")?;
+
+    Ok(String::from_utf8(token)?)
+}
+
+/// Encrypt token with passphrase from JIRALOG_PASSPHRASE or prompt, readable by owner only
+pub fn write_encrypted(profile: Option<&str>, token: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(&passphrase(true)?, &salt)?);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, token.as_bytes())
+        .map_err(|_| "Encrypting token failed")?;
+
+    let path = get_token_path(profile)?;
+    fs::write(&path, BASE64_STANDARD.encode([&salt[..], &nonce[..], &ciphertext].concat()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(path)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key, Box<dyn Error>> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut_slice())
+        .map_err(|err| format!("Deriving key from passphrase failed: {}", err))?;
+
+    Ok(key)
+}
+
+/// Passphrase is given in environment, encrypting and decrypting need no prompt
+pub fn has_passphrase_env() -> bool {
+    env::var_os(PASSPHRASE_ENV).is_some()
+}
+
+fn passphrase(confirm: bool) -> Result<String, Box<dyn Error>> {
+    if let Ok(passphrase) = env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+
+    let passphrase = prompt_secret("Enter passphrase for token file: ")?;
+
+    if passphrase.is_empty() {
+        return Err(format!("Empty passphrase, enter passphrase or set {}", PASSPHRASE_ENV).into());
+    }
+
+    if confirm && passphrase != prompt_secret("Repeat passphrase: ")? {
+        return Err("Passphrases do not match".into());
+    }
+
+    Ok(passphrase)
+}
+
+/// Read secret without echo from terminal, or line from piped stdin
+pub fn prompt_secret(prompt: &str) -> Result<String, Box<dyn Error>> {
+    if stdin().is_terminal() {
+        Ok(rpassword::prompt_password(prompt)?)
+    } else {
+        print!("{}", prompt);
+        stdout().flush()?;
+
+        let mut input = String::new();
+        stdin().read_line(&mut input)?;
+
+        Ok(input.trim_end().to_string())
+    }
+}
@@ -1,126 +1,273 @@
-use chrono::{DateTime, FixedOffset, Local, Utc};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, TimeDelta, Timelike};
 use indicatif::{ProgressBar, ProgressStyle};
 use inline_colorization::*;
-use java_properties::read;
-use java_properties::write;
-use lazy_static::lazy_static;
-use std::collections::HashMap;
-use std::error::Error;
-use std::fs::{self, File, OpenOptions};
-use std::io::stdin;
-use std::io::BufReader;
-use std::io::BufWriter;
-use std::io::{stdout, BufRead, Cursor, Seek, Write};
-use std::path::PathBuf;
-
+use std::collections::{HashMap, HashSet};
+
+use std::fs;
+use std::io::{stdout, Cursor};
+
+use crate::check::{find_gaps, find_overlaps, Gap, Interval, Overlap};
+use crate::duration::{TimeSpent, WorkingTime};
+use crate::error::JiralogError;
+use crate::config::{
+    active_profile, list_profiles, profile_name, read_config, read_daily_target, read_tracking_rules,
+    read_working_time,
+};
 use crate::editor::run_editor;
-use crate::jira::update_time_spent;
-use crate::jira::validate_jira_time_spent;
+use crate::jira::{JiraClient, RemoteWorklog};
 use crate::model::Configuration;
-use crate::model::{self, WorklogMessage, WorklogRecord};
+use crate::output::{is_json, print_json};
+use crate::model::{self, ShortEntries, WorklogMessage, WorklogRecord, CURRENT_MARKER};
+use crate::report::{GroupBy, Report};
+use crate::paths::{
+    get_commit_path, get_config_dir_path, get_config_path, get_data_dir_path, get_lock_path,
+    get_migrated_worklog_path, get_worklog_db_path, get_worklog_export_path, get_worklog_path,
+};
+use crate::store::{CsvStore, SqliteStore, WorklogLock, WorklogStore};
+
+/// Items started this many days before an item are compared for overlaps, longer items are not
+static OVERLAP_LOOKBACK_DAYS: i64 = 7;
+
+/// Sqlite store once worklog has been migrated, csv store otherwise
+fn open_store() -> Result<Box<dyn WorklogStore>, JiralogError> {
+    let db_path = get_worklog_db_path()?;
+
+    if db_path.exists() {
+        Ok(Box::new(SqliteStore::open(db_path, get_worklog_export_path()?)?))
+    } else {
+        Ok(Box::new(CsvStore::new(get_worklog_path()?)))
+    }
+}
 
-static WORKLOG_FILE: &str = "worklog.csv";
-static COMMIT_FILE: &str = "commit_worklog";
+/// Run read-modify-write cycle holding exclusive worklog lock
+fn modify_store<T, F>(op: F) -> Result<T, JiralogError>
+where
+    F: FnOnce(&dyn WorklogStore) -> Result<T, JiralogError>,
+{
+    let _lock = WorklogLock::exclusive(&get_lock_path()?)?;
+    op(open_store()?.as_ref())
+}
 
-lazy_static! {
-    static ref CONFIG: Configuration = read_config().expect("Unable to load configuration");
-    static ref CURRENT_MARKER: String = "current".to_string();
+/// Read worklog holding shared worklog lock
+fn read_store<T, F>(op: F) -> Result<T, JiralogError>
+where
+    F: FnOnce(&dyn WorklogStore) -> Result<T, JiralogError>,
+{
+    let _lock = WorklogLock::shared(&get_lock_path()?)?;
+    op(open_store()?.as_ref())
 }
 
-pub fn worklog_path() -> String {
-    get_worklog_path()
+/// Worklog as csv file for viewing, exported from the store when needed
+pub fn worklog_csv_path() -> Result<String, JiralogError> {
+    let path = read_store(|store| Ok(store.export_csv()?))?;
+
+    Ok(path
         .to_str()
-        .expect("No csv path")
-        .to_string()
+        .ok_or_else(|| JiralogError::Other("No csv path".to_string()))?
+        .to_string())
 }
 
+/// Add item, overlaps with other items are warned about or refused when strict
 pub fn add(
-    ticket: &String,
-    time_spent: &String,
-    description: &String,
+    ticket: &str,
+    time_spent: &str,
+    description: &str,
+    started_date: &DateTime<FixedOffset>,
+    strict: bool,
+) -> Result<AddedWorklog, JiralogError> {
+    modify_store(|store| {
+        let item = new_item(ticket, time_spent, description, started_date)?;
+        let overlaps = overlaps_in_store(store, std::slice::from_ref(&item), false)?;
+
+        if strict {
+            refuse_overlaps(&overlaps)?;
+        }
+        warn_overlaps(&overlaps);
+
+        store.insert(&item)?;
+
+        Ok(AddedWorklog { added: item, overlaps })
+    })
+}
+
+fn add_item(
+    store: &dyn WorklogStore,
+    ticket: &str,
+    time_spent: &str,
+    description: &str,
     started_date: &DateTime<FixedOffset>,
-) -> Result<WorklogRecord, Box<dyn Error>> {
-    validate_jira_time_spent(time_spent)?;
+) -> Result<WorklogRecord, JiralogError> {
+    let item = new_item(ticket, time_spent, description, started_date)?;
 
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(get_worklog_path())
-        .unwrap();
+    store.insert(&item)?;
 
-    let needs_headers = file.seek(std::io::SeekFrom::End(0))? == 0;
-    let mut writer = csv::WriterBuilder::new()
-        .has_headers(needs_headers)
-        .from_writer(file);
+    Ok(item)
+}
 
+fn new_item(
+    ticket: &str,
+    time_spent: &str,
+    description: &str,
+    started_date: &DateTime<FixedOffset>,
+) -> Result<WorklogRecord, JiralogError> {
     let id = model::get_nano_id();
 
-    let item = WorklogRecord {
-        ticket: ticket.clone(),
-        time_spent: time_spent.clone(),
-        description: description.clone(),
-        started_date: started_date.clone(),
+    let mut item = WorklogRecord {
+        ticket: ticket.to_string(),
+        time_spent: time_spent.to_string(),
+        description: description.to_string(),
+        started_date: *started_date,
         committed: false,
-        id: id.clone(),
+        id,
+        remote_id: None,
+        profile: active_profile(),
+        minutes: None,
+        paused_at: None,
+        paused_seconds: 0,
+        resumed_at: None,
     };
 
-    writer.serialize(&item)?;
+    if time_spent != CURRENT_MARKER {
+        let working_time = read_working_time(active_profile().as_deref())?;
+        item.set_time_spent(TimeSpent::parse(time_spent, &working_time)?);
+    }
 
     Ok(item)
 }
 
-pub fn remove(id: &String) -> Result<String, Box<dyn Error>> {
-    let mut worklog = read_worklog()?;
-    if let Some(item_position) = worklog.iter().position(|v| &v.id == id) {
-        worklog.remove(item_position);
-        write_worklog(worklog)?;
+/// Remove item, committed item is deleted from Jira too unless local only
+/// Jira is called without holding worklog lock, item is removed locally after Jira delete succeeds
+pub fn remove(id: &str, local_only: bool) -> Result<WorklogRecord, JiralogError> {
+    let item = read_store(|store| Ok(store.find(id)?))?
+        .ok_or_else(|| JiralogError::NotFound(format!("No worklog item {}", id)))?;
 
-        Ok(id.clone())
-    } else {
-        Err(format!("No worklog item {}", id).into())
-    }
+    remove_item(item, local_only)
 }
 
-pub fn pop() -> Result<Option<WorklogRecord>, Box<dyn Error>> {
-    let mut worklog = read_worklog()?;
+pub fn pop(local_only: bool) -> Result<Option<WorklogRecord>, JiralogError> {
+    read_store(|store| Ok(store.last()?))?
+        .map(|item| remove_item(item, local_only))
+        .transpose()
+}
 
-    let item = worklog.pop();
-    write_worklog(worklog)?;
+fn remove_item(item: WorklogRecord, local_only: bool) -> Result<WorklogRecord, JiralogError> {
+    if !local_only {
+        delete_remote(&item)?;
+    }
+    modify_store(|store| Ok(store.remove(&item.id)?))?;
 
     Ok(item)
 }
 
-pub fn begin(ticket: &String, description: &String) -> Result<BeginWorklog, Box<dyn Error>> {
-    let current_ticket_id = current_ticket()?.map(|v| v.id);
+fn delete_remote(item: &WorklogRecord) -> Result<(), JiralogError> {
+    if item.committed {
+        let remote_id = remote_id(item, "remove it with --local and delete it in Jira")?;
+        let config = read_config(item.profile.as_deref())?;
+
+        JiraClient::new(&config)?
+            .delete_worklog(&item.ticket, remote_id)
+            .map_err(|err| err.context(&format!("{} delete from Jira failed", item.id)))?;
+    }
+
+    Ok(())
+}
 
-    end_current()?;
+/// Jira worklog id of committed item, items committed before ids were stored have none
+fn remote_id<'a>(item: &'a WorklogRecord, fix: &str) -> Result<&'a str, JiralogError> {
+    item.remote_id.as_deref().ok_or_else(|| {
+        JiralogError::Validation(format!("{} has no Jira worklog id, {}", item.id, fix))
+    })
+}
 
-    let added = add(
-        ticket,
-        &CURRENT_MARKER,
-        description,
-        &Local::now().fixed_offset(),
-    )?;
+/// Edit item, committed item is updated in Jira too
+pub fn edit(
+    id: &str,
+    time_spent: &Option<String>,
+    started_date: &Option<DateTime<FixedOffset>>,
+    description: &Option<String>,
+) -> Result<WorklogRecord, JiralogError> {
+    if time_spent.is_none() && started_date.is_none() && description.is_none() {
+        return Err(JiralogError::Validation(
+            "Nothing to edit, provide time spent, started date or description".to_string(),
+        ));
+    }
 
-    let previous = current_ticket_id.map(find_item).transpose()?.flatten();
+    let not_found = || JiralogError::NotFound(format!("No worklog item {}", id));
+    let mut item = read_store(|store| Ok(store.find(id)?))?.ok_or_else(not_found)?;
 
-    Ok(BeginWorklog {
-        previous,
-        current: added,
+    if let Some(value) = time_spent {
+        let working_time = read_working_time(item.profile.as_deref())?;
+        item.set_time_spent(TimeSpent::parse(value, &working_time)?);
+    }
+    if let Some(value) = started_date {
+        item.started_date = *value;
+    }
+    if let Some(value) = description {
+        item.description = value.clone();
+    }
+
+    // Jira is called without holding worklog lock
+    if item.committed {
+        let remote_id = remote_id(&item, "edit committed item in Jira")?;
+        let config = read_config(item.profile.as_deref())?;
+
+        JiraClient::new(&config)?
+            .update_worklog(&item, remote_id)
+            .map_err(|err| err.context(&format!("{} update in Jira failed", item.id)))?;
+    }
+
+    if !modify_store(|store| Ok(store.update(&item)?))? {
+        return Err(not_found());
+    }
+
+    Ok(item)
+}
+
+/// Begin item at given time, now by default, previous item in progress ends at same time
+pub fn begin(
+    ticket: &str,
+    description: &str,
+    at: &Option<DateTime<FixedOffset>>,
+) -> Result<BeginWorklog, JiralogError> {
+    let at = tracking_time(at)?;
+
+    modify_store(|store| {
+        let previous = end_current_item(store, &at)?;
+
+        let added = add_item(store, ticket, CURRENT_MARKER, description, &at)?;
+
+        Ok(BeginWorklog {
+            previous,
+            current: added,
+        })
     })
 }
 
-pub fn print_current_ticket(format: &Option<String>) -> Result<WorklogMessage, Box<dyn Error>> {
+pub fn print_current_ticket(format: &Option<String>) -> Result<WorklogMessage, JiralogError> {
+    if is_json() {
+        let current = current_ticket()?.map(|mut item| {
+            item.set_time_spent(get_current_duration(&item));
+            item
+        });
+        print_json(&CurrentDocument { current })?;
+
+        return empty_ok();
+    }
+
     match current_ticket()? {
         Some(value) => {
             let print_format = format
                 .clone()
                 .unwrap_or("[%ti]: time spent=%ts".to_string());
 
-            let msg = print_format
+            let mut msg = print_format
                 .replace("%ti", &value.ticket)
                 .replace("%d", &value.description)
-                .replace("%ts", &get_current_duration(&value));
+                .replace("%ts", &get_current_duration(&value).to_string());
+
+            if format.is_none() && value.paused_at.is_some() {
+                msg.push_str(" (paused)");
+            }
 
             Ok(WorklogMessage(msg))
         }
@@ -129,145 +276,562 @@ pub fn print_current_ticket(format: &Option<String>) -> Result<WorklogMessage, B
     }
 }
 
-pub fn worklog_to_stdout() -> Result<WorklogMessage, Box<dyn Error>> {
-    let file = File::open(get_worklog_path())?;
-    let reader = BufReader::new(file);
+pub fn worklog_to_stdout(
+    ticket: &Option<String>,
+    date: &Option<NaiveDate>,
+) -> Result<WorklogMessage, JiralogError> {
+    let worklog = read_store(|store| match (ticket, date) {
+        (_, Some(date)) => {
+            let from = model::start_of_day(date).map_err(JiralogError::Validation)?;
+            let to = model::end_of_day(date).map_err(JiralogError::Validation)?;
+            Ok(store
+                .find_by_started_date(&from, &to)?
+                .into_iter()
+                .filter(|v| ticket.as_ref().is_none_or(|ticket| &v.ticket == ticket))
+                .collect())
+        }
+        (Some(ticket), None) => Ok(store.find_by_ticket(ticket)?),
+        (None, None) => Ok(store.read_all()?),
+    })?;
+
+    if is_json() {
+        print_json(&worklog)?;
 
-    for line in reader.lines() {
-        let v = line?;
-        println!("{}", v);
+        return empty_ok();
     }
 
+    let mut writer = csv::WriterBuilder::new().from_writer(stdout());
+    worklog.iter().try_for_each(|v| writer.serialize(v))?;
+    writer.flush()?;
+
     empty_ok()
 }
 
-fn get_current_duration(record: &WorklogRecord) -> String {
-    let now = Utc::now();
-    let delta = now.signed_duration_since(record.started_date);
-    let delta_minutes = delta.num_minutes();
-
-    format!("{}m", delta_minutes)
+/// Time since start of item in progress without paused time, ongoing pause counts until now
+fn get_current_duration(record: &WorklogRecord) -> TimeSpent {
+    duration_until(record, &Local::now().fixed_offset())
 }
 
-fn current_ticket() -> Result<Option<WorklogRecord>, Box<dyn Error>> {
-    let current = read_worklog()?
-        .into_iter()
-        .find(|item| item.time_spent == *CURRENT_MARKER);
+fn duration_until(record: &WorklogRecord, until: &DateTime<FixedOffset>) -> TimeSpent {
+    let until = record.paused_at.map_or(*until, |paused_at| paused_at.min(*until));
+    let delta = until.signed_duration_since(record.started_date)
+        - TimeDelta::seconds(record.paused_seconds);
+    let delta_minutes = delta.num_minutes().clamp(0, u32::MAX as i64) as u32;
 
-    Ok(current)
+    TimeSpent::from_minutes(delta_minutes)
 }
 
-pub fn end_current() -> Result<Option<WorklogRecord>, Box<dyn Error>> {
-    let mut worklog = read_worklog()?;
-    let result;
+/// Working time of profiles of items without stored minutes, read once per profile
+fn working_times<'a>(items: impl IntoIterator<Item = &'a WorklogRecord>) -> WorkingTimes {
+    let mut working_times = WorkingTimes::new();
 
-    if let Some(item) = worklog
-        .iter_mut()
-        .find(|record| record.time_spent == *CURRENT_MARKER)
-    {
-        item.time_spent = get_current_duration(item);
+    for item in items.into_iter().filter(|v| v.minutes.is_none()) {
+        if !working_times.contains_key(&item.profile) {
+            if let Ok(working_time) = read_working_time(item.profile.as_deref()) {
+                working_times.insert(item.profile.clone(), working_time);
+            }
+        }
+    }
+
+    working_times
+}
 
-        result = Ok(Some(item.clone()))
+/// Minutes of ended item, items from before minutes were stored are parsed with working time
+/// of their profile
+fn item_minutes(item: &WorklogRecord, working_times: &WorkingTimes) -> Option<u32> {
+    item.minutes.or_else(|| {
+        let working_time = working_times.get(&item.profile)?;
+        TimeSpent::parse(&item.time_spent, working_time).ok().map(|v| v.minutes())
+    })
+}
+
+/// Minutes of item, time spent so far for current item
+fn tracked_minutes(item: &WorklogRecord, working_times: &WorkingTimes) -> Option<u32> {
+    if item.time_spent == CURRENT_MARKER {
+        Some(get_current_duration(item).minutes())
     } else {
-        result = Ok(None)
+        item_minutes(item, working_times)
     }
+}
 
-    write_worklog(worklog)?;
+/// Item from started date for time spent, current item for time spent so far
+fn item_interval(item: &WorklogRecord, working_times: &WorkingTimes) -> Option<Interval> {
+    tracked_minutes(item, working_times).map(|minutes| Interval::new(item, minutes))
+}
+
+/// Overlaps of items with each other and with items of store started up to lookback before them.
+/// Committed only compares with committed items of store.
+fn overlaps_in_store(
+    store: &dyn WorklogStore,
+    items: &[WorklogRecord],
+    committed_only: bool,
+) -> Result<Vec<Overlap>, JiralogError> {
+    let working_times = working_times(items);
+    let intervals: Vec<Interval> = items.iter().filter_map(|v| item_interval(v, &working_times)).collect();
+
+    let (Some(from), Some(to)) = (
+        intervals.iter().map(|v| v.start).min(),
+        intervals.iter().map(|v| v.end).max(),
+    ) else {
+        return Ok(vec![]);
+    };
+
+    let ids: HashSet<&str> = items.iter().map(|v| v.id.as_str()).collect();
+    let mut others = items_around(store, &from, &to)?;
+    others.retain(|v| !ids.contains(v.id.as_str()) && (v.committed || !committed_only));
 
-    result
+    Ok(overlaps_of(items, &others))
 }
 
-fn read_worklog() -> Result<Vec<WorklogRecord>, Box<dyn Error>> {
-    if let Ok(file) = File::open(worklog_path()) {
-        let mut rdr = csv::Reader::from_reader(file);
+/// Items that may overlap [from, to), started up to lookback before it, and current item
+fn items_around(
+    store: &dyn WorklogStore,
+    from: &DateTime<FixedOffset>,
+    to: &DateTime<FixedOffset>,
+) -> Result<Vec<WorklogRecord>, JiralogError> {
+    let lookback = *from - TimeDelta::days(OVERLAP_LOOKBACK_DAYS);
+    let mut items = store.find_by_started_date(&lookback, to)?;
+    // Current item may have begun before lookback
+    items.extend(store.current()?.filter(|v| v.started_date < lookback));
+
+    Ok(items)
+}
 
-        let worklog_records: Vec<WorklogRecord> = rdr.deserialize().collect::<Result<_, _>>()?;
+/// Overlaps of items with each other and with other items of worklog
+fn overlaps_of(items: &[WorklogRecord], worklog: &[WorklogRecord]) -> Vec<Overlap> {
+    let ids: HashSet<&str> = items.iter().map(|v| v.id.as_str()).collect();
+    let working_times = working_times(items.iter().chain(worklog));
+    let intervals: Vec<Interval> = items
+        .iter()
+        .chain(worklog.iter().filter(|v| !ids.contains(v.id.as_str())))
+        .filter_map(|v| item_interval(v, &working_times))
+        .collect();
+
+    find_overlaps(&intervals)
+        .into_iter()
+        .filter(|v| ids.contains(v.first.id.as_str()) || ids.contains(v.second.id.as_str()))
+        .collect()
+}
 
-        Ok(worklog_records)
-    } else {
-        Ok(Vec::new())
+fn refuse_overlaps(overlaps: &[Overlap]) -> Result<(), JiralogError> {
+    if overlaps.is_empty() {
+        return Ok(());
     }
+
+    let lines: Vec<String> = overlaps.iter().map(|v| v.to_string()).collect();
+
+    Err(JiralogError::Validation(format!(
+        "{} overlapping items, fix started date or time spent\n{}",
+        overlaps.len(),
+        lines.join("\n")
+    )))
 }
 
-fn read_worklog_uncommitted() -> Result<Vec<WorklogRecord>, Box<dyn Error>> {
-    let worklog = read_worklog()?;
-    Ok(worklog.into_iter().filter(|v| !v.committed).collect())
+/// Warnings go to stderr, json output lists overlaps in document instead
+fn warn_overlaps(overlaps: &[Overlap]) {
+    if !is_json() {
+        for overlap in overlaps {
+            eprintln!("{color_bright_yellow}Warning: {}{color_reset}", overlap);
+        }
+    }
 }
 
-fn find_item(id: String) -> Result<Option<WorklogRecord>, Box<dyn Error>> {
-    let worklog = read_worklog()?;
-    let item = worklog.iter().find(|&v| v.id == id);
+/// Overlaps of items touching day and gaps within working day of active profile
+pub fn check(day: &NaiveDate) -> Result<CheckReport, JiralogError> {
+    let working_time = read_working_time(active_profile().as_deref())?;
+    let from = model::start_of_day(day).map_err(JiralogError::Validation)?;
+    let to = model::end_of_day(day).map_err(JiralogError::Validation)?;
+
+    let items = read_store(|store| items_around(store, &from, &to))?;
+    let working_times = working_times(&items);
+    let intervals: Vec<Interval> = items
+        .iter()
+        .filter_map(|v| item_interval(v, &working_times))
+        .filter(|v| v.end > from && v.start < to)
+        .collect();
+
+    // Days off have no working day to leave gaps in
+    let gaps = if working_time.is_working_day(day) {
+        let day_start = from + TimeDelta::seconds(working_time.day_start.num_seconds_from_midnight() as i64);
+        let day_end = day_start + TimeDelta::minutes((working_time.hours_per_day * 60.0).round() as i64);
+
+        find_gaps(&intervals, &day_start, &day_end)
+    } else {
+        Vec::new()
+    };
+
+    Ok(CheckReport {
+        day: *day,
+        overlaps: find_overlaps(&intervals),
+        gaps,
+    })
+}
 
-    Ok(item.cloned())
+fn current_ticket() -> Result<Option<WorklogRecord>, JiralogError> {
+    read_store(|store| Ok(store.current()?))
 }
 
-fn write_worklog(worklog: Vec<WorklogRecord>) -> Result<(), Box<dyn Error>> {
-    let file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(get_worklog_path())?;
+/// Pause item in progress, paused time is not counted in time spent
+pub fn pause() -> Result<Option<WorklogRecord>, JiralogError> {
+    modify_store(|store| {
+        let Some(mut item) = store.current()? else {
+            return Ok(None);
+        };
 
-    let mut writer = csv::WriterBuilder::new().from_writer(file);
-    worklog.iter().try_for_each(|v| writer.serialize(v))?;
+        if item.paused_at.is_some() {
+            return Err(JiralogError::Validation(format!(
+                "{} is already paused, resume first",
+                item.id
+            )));
+        }
 
-    writer.flush()?;
+        item.paused_at = Some(Local::now().fixed_offset());
+        store.update(&item)?;
 
-    Ok(())
+        Ok(Some(item))
+    })
 }
 
-pub fn configure() -> Result<WorklogMessage, Box<dyn Error>> {
-    let read_stdin = |msg: String| {
-        print!("{}", msg);
-        stdout().flush().unwrap();
+pub fn resume() -> Result<Option<WorklogRecord>, JiralogError> {
+    modify_store(|store| {
+        let Some(mut item) = store.current()? else {
+            return Ok(None);
+        };
+
+        if item.paused_at.is_none() {
+            return Err(JiralogError::Validation(format!("{} is not paused", item.id)));
+        }
+
+        end_pause(&mut item, &Local::now().fixed_offset());
+        store.update(&item)?;
 
-        let mut input = String::new();
-        stdin().read_line(&mut input).expect("Failed to read line");
-        input.trim_end().to_string()
+        Ok(Some(item))
+    })
+}
+
+/// Add ongoing pause until given time to paused time
+fn end_pause(item: &mut WorklogRecord, at: &DateTime<FixedOffset>) {
+    if let Some(paused_at) = item.paused_at.take() {
+        item.paused_seconds += at.signed_duration_since(paused_at).num_seconds().max(0);
+        item.resumed_at = Some(*at);
+    }
+}
+
+/// End current item at given time, now by default
+pub fn end_current(at: &Option<DateTime<FixedOffset>>) -> Result<Option<EndedWorklog>, JiralogError> {
+    let at = tracking_time(at)?;
+
+    modify_store(|store| end_current_item(store, &at))
+}
+
+/// Begin or end time, now by default, given time must not be in future
+fn tracking_time(at: &Option<DateTime<FixedOffset>>) -> Result<DateTime<FixedOffset>, JiralogError> {
+    let now = Local::now().fixed_offset();
+
+    match at {
+        Some(at) if *at > now => Err(JiralogError::Validation(format!(
+            "Time {} is in the future",
+            at.format("%Y-%m-%d %H:%M")
+        ))),
+        Some(at) => Ok(*at),
+        None => Ok(now),
+    }
+}
+
+/// End current item with time spent rounded by tracking rules of its profile, item below minimum
+/// is kept at minimum, dropped or merged into previous uncommitted item of ticket
+fn end_current_item(
+    store: &dyn WorklogStore,
+    at: &DateTime<FixedOffset>,
+) -> Result<Option<EndedWorklog>, JiralogError> {
+    let Some(mut item) = store.current()? else {
+        return Ok(None);
     };
 
-    let user = read_stdin("Enter Jira user: ".to_string());
-    let token = read_stdin("Enter Jira token: ".to_string());
-    let instance = read_stdin("Enter Jira cloud instance: ".to_string());
+    if *at < item.started_date {
+        return Err(JiralogError::Validation(format!(
+            "End {} is before start {} of {}",
+            at.format("%Y-%m-%d %H:%M"),
+            item.started_date.format("%Y-%m-%d %H:%M"),
+            item.id
+        )));
+    }
+
+    // Paused time is kept as total only, ending within a completed pause would subtract all of it
+    if let Some(resumed_at) = item.resumed_at.filter(|v| at < v) {
+        return Err(JiralogError::Validation(format!(
+            "End {} is before {} was resumed at {}",
+            at.format("%Y-%m-%d %H:%M"),
+            item.id,
+            resumed_at.format("%Y-%m-%d %H:%M")
+        )));
+    }
+
+    let rules = read_tracking_rules(item.profile.as_deref())?;
+    end_pause(&mut item, at);
+    let minutes = duration_until(&item, at).minutes();
+    let rounded = rules.round(minutes);
 
-    let jiralog_dir = get_config_dir_path();
+    if !rules.is_short(rounded) || rules.short_entries == ShortEntries::Keep {
+        item.set_time_spent(TimeSpent::from_minutes(rounded.max(rules.minimum_minutes)));
+        store.update(&item)?;
 
-    if !jiralog_dir.exists() {
-        fs::create_dir_all(&jiralog_dir)?;
+        return Ok(Some(EndedWorklog::new(item, EndOutcome::Kept, None)));
     }
 
-    let config_path = get_config_path();
+    item.set_time_spent(TimeSpent::from_minutes(minutes));
 
-    let mut config_map = HashMap::new();
-    config_map.insert("token".to_string(), token);
-    config_map.insert("jira_cloud_instance".to_string(), instance);
-    config_map.insert("user".to_string(), user);
+    if rules.short_entries == ShortEntries::Drop {
+        store.remove(&item.id)?;
 
-    let file = File::create(&config_path)?;
-    write(BufWriter::new(file), &config_map)?;
+        return Ok(Some(EndedWorklog::new(item, EndOutcome::Dropped, None)));
+    }
+
+    let candidates: Vec<WorklogRecord> = store
+        .find_by_ticket(&item.ticket)?
+        .into_iter()
+        .filter(|v| !v.committed && v.id != item.id && v.profile == item.profile)
+        .collect();
+    let working_times = working_times(&candidates);
+    let previous = candidates
+        .into_iter()
+        .filter_map(|v| item_minutes(&v, &working_times).map(|minutes| (v, minutes)))
+        .next_back();
+
+    match previous {
+        Some((mut previous, previous_minutes)) => {
+            previous.set_time_spent(TimeSpent::from_minutes(
+                rules.round(previous_minutes + minutes).max(rules.minimum_minutes),
+            ));
+            store.update(&previous)?;
+            store.remove(&item.id)?;
+
+            Ok(Some(EndedWorklog::new(item, EndOutcome::Merged, Some(previous.id))))
+        }
+        None => {
+            item.set_time_spent(TimeSpent::from_minutes(rules.minimum_minutes));
+            store.update(&item)?;
+
+            Ok(Some(EndedWorklog::new(item, EndOutcome::Kept, None)))
+        }
+    }
+}
+
+/// One-shot migration of csv worklog into sqlite, csv is kept as backup
+pub fn migrate() -> Result<WorklogMessage, JiralogError> {
+    let _lock = WorklogLock::exclusive(&get_lock_path()?)?;
+    let db_path = get_worklog_db_path()?;
+
+    if db_path.exists() {
+        return Err(JiralogError::Validation(format!(
+            "Worklog already migrated to {}",
+            db_path.display()
+        )));
+    }
+
+    let csv_path = get_worklog_path()?;
+    let worklog = CsvStore::new(csv_path.clone()).read_all()?;
+
+    let sqlite_store = SqliteStore::open(db_path.clone(), get_worklog_export_path()?)?;
+    sqlite_store.insert_all(&worklog)?;
+
+    if csv_path.exists() {
+        fs::rename(&csv_path, get_migrated_worklog_path()?)?;
+    }
 
     Ok(WorklogMessage(format!(
-        "All good! Wrote {}/jiralog.properties",
-        jiralog_dir.display()
+        "Migrated {} items to {}",
+        worklog.len(),
+        db_path.display()
     )))
 }
 
-pub fn commit() -> Result<WorklogMessage, Box<dyn Error>> {
-    end_current()?;
-    let worklog_uncommitted: Vec<WorklogRecord> = read_worklog_uncommitted()?;
+/// Merge own worklogs started between dates from Jira of active profile as committed items,
+/// matched by Jira worklog id
+pub fn pull(from: &NaiveDate, to: &NaiveDate) -> Result<WorklogMessage, JiralogError> {
+    let from_date = model::start_of_day(from).map_err(JiralogError::Validation)?;
+    let to_date = model::end_of_day(to).map_err(JiralogError::Validation)?;
+
+    if from_date >= to_date {
+        return Err(JiralogError::Validation(
+            "From date must not be after to date".to_string(),
+        ));
+    }
+
+    let profile = active_profile();
+    let config = read_config(profile.as_deref())?;
+    let jira = JiraClient::new(&config)?;
+    let user = jira.myself()?;
+    let updated_ids = jira.updated_worklog_ids(&from_date)?;
+
+    let remote_worklogs: Vec<RemoteWorklog> = jira.worklogs_by_ids(&updated_ids)?
+        .into_iter()
+        .filter(|v| v.author.is_same(&user))
+        .collect();
+
+    let mut issue_keys: HashMap<String, String> = HashMap::new();
+    let mut pulled = Vec::new();
+
+    for remote in remote_worklogs {
+        let started_date = remote.started_date()?;
+
+        if started_date < from_date || started_date >= to_date {
+            continue;
+        }
+
+        if !issue_keys.contains_key(&remote.issue_id) {
+            let key = jira.issue_key(&remote.issue_id)?;
+            issue_keys.insert(remote.issue_id.clone(), key);
+        }
+
+        let time_spent = remote.time_spent(&config.working_time)?;
+
+        pulled.push(WorklogRecord {
+            ticket: issue_keys[&remote.issue_id].clone(),
+            time_spent: time_spent.to_string(),
+            description: remote.description(),
+            started_date,
+            committed: true,
+            id: model::get_nano_id(),
+            remote_id: Some(remote.id),
+            profile: profile.clone(),
+            minutes: Some(time_spent.minutes()),
+            paused_at: None,
+            paused_seconds: 0,
+            resumed_at: None,
+        });
+    }
+
+    let (added, updated) = modify_store(|store| {
+        let mut added = 0;
+        let mut updated = 0;
+
+        for item in &pulled {
+            let remote_id = item.remote_id.as_deref().unwrap_or_default();
+
+            match store.find_by_remote_id(remote_id, profile.as_deref())? {
+                Some(existing) => {
+                    store.update(&WorklogRecord {
+                        id: existing.id,
+                        ..item.clone()
+                    })?;
+                    updated += 1;
+                }
+                None => {
+                    store.insert(item)?;
+                    added += 1;
+                }
+            }
+        }
+
+        Ok((added, updated))
+    })?;
+
+    Ok(WorklogMessage(format!(
+        "Pulled {} worklogs, added {}, updated {}",
+        pulled.len(),
+        added,
+        updated
+    )))
+}
+
+/// Working time by profile
+type WorkingTimes = HashMap<Option<String>, WorkingTime>;
+
+/// Committed item with outcome of sending it to Jira
+type CommitResult<'a> = (&'a WorklogRecord, Result<WorklogRecord, JiralogError>);
+
+/// Configuration for each profile of items, keyed by profile
+fn read_profile_configs(
+    items: &[WorklogRecord],
+) -> Result<HashMap<Option<String>, Configuration>, JiralogError> {
+    let mut configs = HashMap::new();
+
+    for item in items {
+        if !configs.contains_key(&item.profile) {
+            configs.insert(item.profile.clone(), read_config(item.profile.as_deref())?);
+        }
+    }
+
+    Ok(configs)
+}
+
+/// Jira client for each profile
+fn profile_clients(
+    configs: &HashMap<Option<String>, Configuration>,
+) -> Result<HashMap<Option<String>, JiraClient<'_>>, JiralogError> {
+    configs
+        .iter()
+        .map(|(profile, config)| Ok((profile.clone(), JiraClient::new(config)?)))
+        .collect()
+}
+
+/// Commit uncommitted items to Jira instance of their profile. Overlaps with each other and with
+/// committed items are warned about or refused when strict. Dry run prints requests instead and
+/// leaves worklog untouched.
+pub fn commit(dry_run: bool, strict: bool) -> Result<WorklogMessage, JiralogError> {
+    let worklog_uncommitted: Vec<WorklogRecord> = if dry_run {
+        read_store(|store| Ok(store.read_uncommitted()?))?
+            .into_iter()
+            .map(|mut item| {
+                // Current item as end would keep it, dropped and merged items are not predicted
+                if item.time_spent == CURRENT_MARKER {
+                    let rules = read_tracking_rules(item.profile.as_deref())?;
+                    let minutes = rules.round(get_current_duration(&item).minutes());
+                    item.set_time_spent(TimeSpent::from_minutes(minutes.max(rules.minimum_minutes)));
+                }
+                Ok(item)
+            })
+            .collect::<Result<_, JiralogError>>()?
+    } else {
+        modify_store(|store| {
+            end_current_item(store, &Local::now().fixed_offset())?;
+            Ok(store.read_uncommitted()?)
+        })?
+    };
 
     if !worklog_uncommitted.is_empty() {
         let commit_worklog = run_editor(
             worklog_uncommitted.iter().collect(),
-            &CONFIG.get_editor_command(),
-            &get_commit_path(),
+            &read_config(active_profile().as_deref())?.get_editor_command(),
+            &get_commit_path()?,
         )?;
 
         if commit_worklog.is_empty() {
-            return Ok(WorklogMessage("Abort commit".to_string()));
+            return nothing_committed("Abort commit");
         }
 
-        let pb = ProgressBar::new(worklog_uncommitted.len() as u64);
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(Cursor::new(commit_worklog.join("\n")));
+        let mut to_commit: Vec<WorklogRecord> = rdr.deserialize().collect::<Result<_, _>>()?;
+
+        let configs = read_profile_configs(&to_commit)?;
+        let clients = profile_clients(&configs)?;
+
+        // Time spent may be edited in editor, minutes follow it
+        for item in to_commit.iter_mut() {
+            let time_spent = TimeSpent::parse(&item.time_spent, &configs[&item.profile].working_time)
+                .map_err(|err| err.context(&item.id))?;
+            item.set_time_spent(time_spent);
+        }
+
+        let overlaps = read_store(|store| overlaps_in_store(store, &to_commit, true))?;
+
+        if strict {
+            refuse_overlaps(&overlaps)?;
+        }
+        warn_overlaps(&overlaps);
+
+        if dry_run {
+            return print_dry_run(&to_commit, &clients, overlaps);
+        }
+
+        let pb = if is_json() {
+            ProgressBar::hidden()
+        } else {
+            ProgressBar::new(to_commit.len() as u64)
+        };
         pb.set_style(
             ProgressStyle::with_template(
                 "{spinner:.white} {msg:15} [{bar:80.white/gray}] ({pos}/{len})",
@@ -275,52 +839,162 @@ pub fn commit() -> Result<WorklogMessage, Box<dyn Error>> {
             .unwrap(),
         );
 
-        let update = |item: &WorklogRecord| -> Result<(), Box<dyn Error>> {
-            pb.set_message(item.ticket.clone());
-
-            update_time_spent(&CONFIG.get_jira_url(), &CONFIG.user, &CONFIG.token, item)
-                .map_err(|err| format!("{} failed: {}", item.id, err))
-                .map(|_| ())?;
+        // Failed item stays uncommitted, next commit picks it up again
+        let update = |item: &WorklogRecord| -> Result<WorklogRecord, JiralogError> {
+            let remote_id = clients[&item.profile].update_time_spent(item)?;
 
             let commit_item = WorklogRecord {
                 committed: true,
+                remote_id: Some(remote_id),
                 ..item.clone()
             };
 
-            update_item(&commit_item)?;
+            modify_store(|store| Ok(store.update(&commit_item)?))?;
 
-            pb.inc(1);
-
-            Ok(())
+            Ok(commit_item)
         };
 
-        let mut rdr = csv::ReaderBuilder::new()
-            .has_headers(true)
-            .from_reader(Cursor::new(commit_worklog.join("\n")));
-        let to_commit: Vec<WorklogRecord> = rdr.deserialize().collect::<Result<_, _>>()?;
+        let results: Vec<CommitResult> = to_commit
+            .iter()
+            .map(|item| {
+                pb.set_message(item.ticket.clone());
+                let result = update(item);
+                pb.inc(1);
 
-        to_commit.iter().try_for_each(update)?;
+                (item, result)
+            })
+            .collect();
 
-        Ok(WorklogMessage("All done".to_string()))
+        pb.finish_and_clear();
+
+        if is_json() {
+            print_json(&CommitDocument::from_results(&results, overlaps))?;
+        } else {
+            print_commit_results(&results);
+        }
+
+        let total = results.len();
+        let mut errors: Vec<JiralogError> =
+            results.into_iter().filter_map(|(_, result)| result.err()).collect();
+
+        if errors.is_empty() && is_json() {
+            empty_ok()
+        } else if errors.is_empty() {
+            Ok(WorklogMessage(format!("All done, committed {} items", total)))
+        } else {
+            Err(JiralogError::Commit {
+                failed: errors.len(),
+                total,
+                cause: Box::new(errors.swap_remove(0)),
+            })
+        }
     } else {
-        Ok(WorklogMessage("Nothing to commit".to_string()))
+        nothing_committed("Nothing to commit")
     }
 }
 
-pub fn update_item(item: &WorklogRecord) -> Result<(), Box<dyn Error>> {
-    let mut worklog = read_worklog()?;
+fn nothing_committed(message: &str) -> Result<WorklogMessage, JiralogError> {
+    if is_json() {
+        print_json(&CommitDocument::from_results(&[], vec![]))?;
 
-    if let Some(index) = worklog.iter().position(|r| r.id == item.id) {
-        worklog[index] = item.clone();
-        write_worklog(worklog)?;
+        return empty_ok();
     }
 
-    Ok(())
+    Ok(WorklogMessage(message.to_string()))
 }
 
-pub fn print_info() -> Result<WorklogMessage, Box<dyn Error>> {
-    let items = read_worklog()?;
-    let uncommitted_items = read_worklog_uncommitted()?;
+fn print_dry_run(
+    to_commit: &[WorklogRecord],
+    clients: &HashMap<Option<String>, JiraClient>,
+    overlaps: Vec<Overlap>,
+) -> Result<WorklogMessage, JiralogError> {
+    if is_json() {
+        let dry_run = to_commit
+            .iter()
+            .map(|item| {
+                Ok(DryRunRequest {
+                    item: item.clone(),
+                    request: clients[&item.profile].describe_update_time_spent(item)?,
+                })
+            })
+            .collect::<Result<Vec<_>, JiralogError>>()?;
+        print_json(&DryRunDocument { dry_run, overlaps })?;
+
+        return empty_ok();
+    }
+
+    for item in to_commit {
+        println!(
+            "{}: ticket={}, profile={}",
+            item.id,
+            item.ticket,
+            profile_name(item.profile.as_deref())
+        );
+        println!("{}", clients[&item.profile].describe_update_time_spent(item)?);
+        println!();
+    }
+
+    Ok(WorklogMessage(format!(
+        "Dry run, {} items not committed",
+        to_commit.len()
+    )))
+}
+
+fn print_commit_results(results: &[CommitResult]) {
+    let ticket_width = results
+        .iter()
+        .map(|(item, _)| item.ticket.len())
+        .max()
+        .unwrap_or(0)
+        .max("TICKET".len());
+
+    println!(
+        "{:<6}  {:<10}  {:<ticket_width$}  {:<10}  ERROR",
+        "STATUS", "ID", "TICKET", "TIME SPENT"
+    );
+
+    for (item, result) in results {
+        let (color, status, error) = match result {
+            Ok(_) => (color_bright_green, "ok", String::new()),
+            Err(err) => (color_bright_red, "failed", err.to_string()),
+        };
+
+        println!(
+            "{color}{:<6}{color_reset}  {:<10}  {:<ticket_width$}  {:<10}  {}",
+            status, item.id, item.ticket, item.time_spent, error
+        );
+    }
+
+    println!();
+}
+
+pub fn print_info() -> Result<WorklogMessage, JiralogError> {
+    let (items, uncommitted_items) =
+        read_store(|store| Ok((store.read_all()?, store.read_uncommitted()?)))?;
+
+    let working_times = working_times(&uncommitted_items);
+    let uncommitted_minutes: u32 =
+        uncommitted_items.iter().filter_map(|v| item_minutes(v, &working_times)).sum();
+
+    if is_json() {
+        let worklog_path = get_worklog_db_path()?;
+
+        print_json(&InfoDocument {
+            home: get_config_dir_path()?.display().to_string(),
+            data: get_data_dir_path()?.display().to_string(),
+            configuration: get_config_path(active_profile().as_deref())?.display().to_string(),
+            profile: profile_name(active_profile().as_deref()).to_string(),
+            profiles: list_profiles()?,
+            worklog: if worklog_path.exists() { worklog_path } else { get_worklog_path()? }
+                .display()
+                .to_string(),
+            total_items: items.len(),
+            uncommitted_items: uncommitted_items.len(),
+            uncommitted_minutes,
+        })?;
+
+        return empty_ok();
+    }
 
     let header = "
      ____.__              .__                 
@@ -336,29 +1010,50 @@ pub fn print_info() -> Result<WorklogMessage, Box<dyn Error>> {
     println!(
         "Jiralog home: 
     {}",
-        get_config_dir_path().display()
+        get_config_dir_path()?.display()
     );
     println!();
 
+    let data_dir = get_data_dir_path()?;
+    if data_dir != get_config_dir_path()? {
+        println!(
+            "Jiralog data: 
+    {}",
+            data_dir.display()
+        );
+        println!();
+    }
+
     println!(
         "Configuration: 
     {}",
-        get_config_path().display()
+        get_config_path(active_profile().as_deref())?.display()
     );
     println!();
 
+    println!(
+        "Profile: 
+    {} (available: {})",
+        profile_name(active_profile().as_deref()),
+        list_profiles()?.join(", ")
+    );
+    println!();
+
+    let worklog_path = get_worklog_db_path()?;
     println!(
         "Worklog: 
     {}",
-        get_worklog_path().display()
+        if worklog_path.exists() { worklog_path } else { get_worklog_path()? }.display()
     );
 
     println!();
 
     println!(
-        "Total items {}, uncommitted items {}",
+        "Total items {}, uncommitted items {}, uncommitted time spent {}",
         items.len(),
-        uncommitted_items.len()
+        uncommitted_items.len(),
+        TimeSpent::from_minutes(uncommitted_minutes)
+            .in_working_time(&read_working_time(active_profile().as_deref())?)
     );
 
     println!("{color_reset}");
@@ -366,73 +1061,218 @@ pub fn print_info() -> Result<WorklogMessage, Box<dyn Error>> {
     empty_ok()
 }
 
-pub fn purge() -> Result<usize, Box<dyn Error>> {
-    let worklog = read_worklog()?;
+/// Time spent of items of all profiles started between days, days under daily target of
+/// active profile are highlighted
+pub fn report(from: &NaiveDate, to: &NaiveDate, group_by: GroupBy) -> Result<WorklogMessage, JiralogError> {
+    if from > to {
+        return Err(JiralogError::Validation(format!("From {} is after to {}", from, to)));
+    }
+
+    let from_date = model::start_of_day(from).map_err(JiralogError::Validation)?;
+    let to_date = model::end_of_day(to).map_err(JiralogError::Validation)?;
+    let items = read_store(|store| Ok(store.find_by_started_date(&from_date, &to_date)?))?;
+    let working_times = working_times(&items);
+    let item_minutes: Vec<(&WorklogRecord, u32)> = items
+        .iter()
+        .filter_map(|v| tracked_minutes(v, &working_times).map(|minutes| (v, minutes)))
+        .collect();
+
+    let profile = active_profile();
+    let working_time = read_working_time(profile.as_deref())?;
+    let report = Report::new(
+        &item_minutes,
+        from,
+        to,
+        group_by,
+        read_daily_target(profile.as_deref())?.minutes(),
+        working_time.working_days(),
+    );
 
-    let uncommitted = read_worklog_uncommitted()?;
-    let uncommitted_length = uncommitted.len();
+    if is_json() {
+        print_json(&report)?;
 
-    write_worklog(uncommitted)?;
+        return empty_ok();
+    }
 
-    Ok(worklog.len() - uncommitted_length)
+    if report.rows.is_empty() {
+        return Ok(WorklogMessage(format!("Nothing to report between {} and {}", from, to)));
+    }
+
+    print_report(&report);
+
+    empty_ok()
 }
 
-fn empty_ok() -> Result<WorklogMessage, Box<dyn Error>> {
-    Ok(WorklogMessage("".to_string()))
+fn print_report(report: &Report) {
+    let time = |minutes: u32| TimeSpent::from_minutes(minutes).to_string();
+    let key_width = report
+        .rows
+        .iter()
+        .map(|v| v.key.len())
+        .max()
+        .unwrap_or(0)
+        .max("TOTAL".len())
+        .max(report.group_by.to_string().len());
+
+    println!(
+        "{:<key_width$}  {:<10}  {:<11}  {:<10}  TARGET",
+        report.group_by.to_string().to_uppercase(),
+        "COMMITTED",
+        "UNCOMMITTED",
+        "TOTAL"
+    );
+
+    for row in &report.rows {
+        let color = if row.under_target { color_bright_red } else { color_reset };
+
+        println!(
+            "{color}{:<key_width$}  {:<10}  {:<11}  {:<10}  {}{color_reset}",
+            row.key,
+            time(row.committed_minutes),
+            time(row.uncommitted_minutes),
+            time(row.total_minutes),
+            row.target_minutes.map(time).unwrap_or_default()
+        );
+    }
+
+    println!(
+        "{:<key_width$}  {:<10}  {:<11}  {}",
+        "TOTAL",
+        time(report.committed_minutes),
+        time(report.uncommitted_minutes),
+        time(report.total_minutes)
+    );
 }
 
-fn get_config_dir_path() -> PathBuf {
-    let home_dir = dirs::home_dir().expect("Could not locate home directory");
+pub fn purge() -> Result<usize, JiralogError> {
+    modify_store(|store| Ok(store.purge_committed()?))
+}
 
-    let mut jiralog_dir = PathBuf::from(&home_dir);
-    jiralog_dir.push(".jiralog");
+fn empty_ok() -> Result<WorklogMessage, JiralogError> {
+    Ok(WorklogMessage("".to_string()))
+}
 
-    jiralog_dir
+/// What end did with current item
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndOutcome {
+    Kept,
+    /// Below minimum and removed
+    Dropped,
+    /// Below minimum and added to previous item of ticket
+    Merged,
 }
 
-fn get_config_path() -> PathBuf {
-    let jiralog_dir = get_config_dir_path();
+/// Ended item, time spent is tracked time for dropped and merged items
+#[derive(serde::Serialize)]
+pub struct EndedWorklog {
+    #[serde(flatten)]
+    pub item: WorklogRecord,
+    pub outcome: EndOutcome,
+    /// Id of item merged into
+    pub merged_into: Option<String>,
+}
 
-    let mut config_path = PathBuf::from(&jiralog_dir);
-    config_path.push("jiralog.properties");
+impl EndedWorklog {
+    fn new(item: WorklogRecord, outcome: EndOutcome, merged_into: Option<String>) -> Self {
+        EndedWorklog {
+            item,
+            outcome,
+            merged_into,
+        }
+    }
+}
 
-    config_path
+/// Added item and items it overlaps
+#[derive(serde::Serialize)]
+pub struct AddedWorklog {
+    pub added: WorklogRecord,
+    pub overlaps: Vec<Overlap>,
 }
 
-fn get_worklog_path() -> PathBuf {
-    let mut config_dir = get_config_dir_path();
-    config_dir.push(WORKLOG_FILE);
+/// Overlaps and gaps of day
+#[derive(serde::Serialize)]
+pub struct CheckReport {
+    pub day: NaiveDate,
+    pub overlaps: Vec<Overlap>,
+    pub gaps: Vec<Gap>,
+}
 
-    config_dir
+#[derive(serde::Serialize)]
+pub struct BeginWorklog {
+    pub previous: Option<EndedWorklog>,
+    pub current: WorklogRecord,
+}
+
+/// JSON output of current
+#[derive(serde::Serialize)]
+struct CurrentDocument {
+    /// Current item, time spent so far instead of marker
+    current: Option<WorklogRecord>,
 }
 
-fn get_commit_path() -> PathBuf {
-    let mut config_dir = get_config_dir_path();
-    config_dir.push(COMMIT_FILE);
+/// JSON output of commit, failed items stay uncommitted
+#[derive(serde::Serialize)]
+struct CommitDocument {
+    committed: Vec<WorklogRecord>,
+    failed: Vec<CommitFailure>,
+    overlaps: Vec<Overlap>,
+}
 
-    config_dir
+#[derive(serde::Serialize)]
+struct CommitFailure {
+    item: WorklogRecord,
+    error: String,
+    exit_code: u8,
 }
 
-fn read_config() -> Result<Configuration, Box<dyn Error>> {
-    let config = File::open(get_config_path())?;
-    let config_map = read(BufReader::new(config))?;
+impl CommitDocument {
+    fn from_results(results: &[CommitResult], overlaps: Vec<Overlap>) -> Self {
+        let mut document = CommitDocument {
+            committed: vec![],
+            failed: vec![],
+            overlaps,
+        };
 
-    let token = config_map.get("token").expect("No token found");
-    let jira_url = config_map.get("jira_url");
-    let jira_cloud_instance = config_map.get("jira_cloud_instance");
-    let user = config_map.get("user").expect("User not configured");
-    let editor = config_map.get("editor");
+        for (item, result) in results {
+            match result {
+                Ok(committed) => document.committed.push(committed.clone()),
+                Err(err) => document.failed.push(CommitFailure {
+                    item: (*item).clone(),
+                    error: err.to_string(),
+                    exit_code: err.exit_code(),
+                }),
+            }
+        }
 
-    Ok(Configuration {
-        token: token.to_string(),
-        jira_url: jira_url.cloned(),
-        jira_cloud_instance: jira_cloud_instance.cloned(),
-        user: user.to_string(),
-        editor: editor.cloned(),
-    })
+        document
+    }
 }
 
-pub struct BeginWorklog {
-    pub previous: Option<WorklogRecord>,
-    pub current: WorklogRecord,
+/// JSON output of commit --dry-run
+#[derive(serde::Serialize)]
+struct DryRunDocument {
+    dry_run: Vec<DryRunRequest>,
+    overlaps: Vec<Overlap>,
+}
+
+#[derive(serde::Serialize)]
+struct DryRunRequest {
+    item: WorklogRecord,
+    /// Request that would be sent to Jira
+    request: String,
+}
+
+/// JSON output of info
+#[derive(serde::Serialize)]
+struct InfoDocument {
+    home: String,
+    data: String,
+    configuration: String,
+    profile: String,
+    profiles: Vec<String>,
+    worklog: String,
+    total_items: usize,
+    uncommitted_items: usize,
+    uncommitted_minutes: u32,
 }
@@ -0,0 +1,245 @@
+mod common;
+
+use common::{FakeJira, Jiralog, ACCOUNT_ID};
+
+#[test]
+fn commit_sends_worklogs_and_marks_committed() {
+    let jira = FakeJira::start(&["ABC-1", "ABC-2"]);
+    let jiralog = Jiralog::configured(&jira);
+
+    jiralog.stdout(&["add", "ABC-1", "1h", "-d", "Planning"]);
+    jiralog.stdout(&["begin", "ABC-2"]);
+
+    let output = jiralog.stdout(&["commit"]);
+    assert!(output.contains("All done, committed 2 items"), "{}", output);
+
+    let worklogs = jira.worklogs();
+    assert_eq!(worklogs.len(), 2);
+    assert_eq!(worklogs[0].issue_key, "ABC-1");
+    assert_eq!(worklogs[0].time_spent, "1h");
+    assert_eq!(worklogs[0].comment["content"][0]["content"][0]["text"], "Planning");
+    assert_eq!(worklogs[1].issue_key, "ABC-2");
+
+    let worklog = jiralog.worklog();
+    assert!(worklog.iter().all(|v| v["committed"] == "true"));
+    assert_eq!(worklog[0]["remote_id"], worklogs[0].id);
+
+    assert!(jiralog.stdout(&["commit"]).contains("Nothing to commit"));
+}
+
+#[test]
+fn commit_uses_api_v2_with_bearer_token() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    let jiralog = Jiralog::new();
+    jiralog.write_config(&format!(
+        "token=personal-token\njira_url={}\napi_version=2\nauth_type=bearer\n",
+        jira.url
+    ));
+
+    jiralog.stdout(&["add", "ABC-1", "1h", "-d", "Plain comment"]);
+    jiralog.stdout(&["commit"]);
+
+    let request = &jira.requests()[0];
+    assert_eq!(request.path, "/rest/api/2/issue/ABC-1/worklog");
+    assert_eq!(request.authorization.as_deref(), Some("Bearer personal-token"));
+    assert_eq!(jira.worklogs()[0].comment, "Plain comment");
+}
+
+#[test]
+fn commit_continues_past_failures_and_retries_failed() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    let jiralog = Jiralog::configured(&jira);
+
+    jiralog.stdout(&["add", "NOPE-1", "1h"]);
+    jiralog.stdout(&["add", "ABC-1", "2h"]);
+
+    let output = jiralog.run(&["commit"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(stdout.contains("Issue does not exist"), "{}", stdout);
+    assert!(stderr.contains("1 of 2 items failed"), "{}", stderr);
+    assert_eq!(jira.worklogs().len(), 1);
+
+    let committed: Vec<String> = jiralog.worklog().iter().map(|v| v["committed"].clone()).collect();
+    assert_eq!(committed, vec!["false", "true"]);
+
+    jiralog.stderr(&["commit"]);
+    assert_eq!(jira.worklogs().len(), 1, "committed item is not sent again");
+}
+
+#[test]
+fn commit_dry_run_leaves_worklog_untouched() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    let jiralog = Jiralog::configured(&jira);
+
+    jiralog.stdout(&["add", "ABC-1", "1h"]);
+
+    let output = jiralog.stdout(&["commit", "--dry-run"]);
+    assert!(output.contains(&format!("POST {}/rest/api/3/issue/ABC-1/worklog", jira.url)), "{}", output);
+    assert!(output.contains("Basic jj:<redacted>"), "{}", output);
+    assert!(!output.contains("secret-token"), "{}", output);
+
+    assert!(jira.requests().is_empty());
+    assert_eq!(jiralog.worklog()[0]["committed"], "false");
+}
+
+#[test]
+fn edit_and_rm_sync_committed_item() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    let jiralog = Jiralog::configured(&jira);
+
+    let id = Jiralog::added_id(&jiralog.stdout(&["add", "ABC-1", "1h"]));
+    jiralog.stdout(&["commit"]);
+
+    jiralog.stdout(&["edit", &id, "--time-spent", "2h"]);
+    assert_eq!(jira.worklogs()[0].time_spent, "2h");
+
+    jiralog.stdout(&["rm", &id]);
+    assert!(jira.worklogs().is_empty());
+    assert!(jiralog.worklog().is_empty());
+}
+
+#[test]
+fn purge_removes_committed_items() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    let jiralog = Jiralog::configured(&jira);
+
+    jiralog.stdout(&["add", "ABC-1", "1h"]);
+    jiralog.stdout(&["commit"]);
+    jiralog.stdout(&["add", "ABC-1", "2h"]);
+
+    assert!(jiralog.stdout(&["purge"]).contains("Removed 1 items"));
+    assert_eq!(jira.worklogs().len(), 1, "purge keeps worklog in Jira");
+
+    let worklog = jiralog.worklog();
+    assert_eq!(worklog.len(), 1);
+    assert_eq!(worklog[0]["time_spent"], "2h");
+}
+
+#[test]
+fn pull_merges_own_worklogs() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    let jiralog = Jiralog::configured(&jira);
+
+    let started = chrono::Local::now().format("%Y-%m-%dT09:00:00.000%z").to_string();
+    jira.add_worklog("ABC-1", "1h 30m", &started, ACCOUNT_ID);
+    jira.add_worklog("ABC-1", "2h", &started, "someone-else");
+
+    assert!(jiralog.stdout(&["pull"]).contains("added 1, updated 0"));
+    assert!(jiralog.stdout(&["pull"]).contains("added 0, updated 1"));
+
+    let worklog = jiralog.worklog();
+    assert_eq!(worklog.len(), 1);
+    assert_eq!(worklog[0]["ticket"], "ABC-1");
+    assert_eq!(worklog[0]["time_spent"], "1h 30m");
+    assert_eq!(worklog[0]["minutes"], "90");
+    assert_eq!(worklog[0]["committed"], "true");
+}
+
+#[test]
+fn commit_routes_items_to_jira_of_their_profile() {
+    let acme = FakeJira::start(&["ACME-1"]);
+    let globex = FakeJira::start(&["GLX-1"]);
+    let jiralog = Jiralog::configured(&acme);
+    jiralog.write_profile_config("globex", &globex);
+
+    jiralog.stdout(&["add", "ACME-1", "1h"]);
+    jiralog.stdout(&["--profile", "globex", "add", "GLX-1", "2h"]);
+
+    let output = jiralog.stdout(&["commit"]);
+    assert!(output.contains("All done, committed 2 items"), "{}", output);
+
+    assert_eq!(acme.worklogs().len(), 1);
+    assert_eq!(acme.worklogs()[0].issue_key, "ACME-1");
+    assert_eq!(globex.worklogs().len(), 1);
+    assert_eq!(globex.worklogs()[0].issue_key, "GLX-1");
+    assert_eq!(
+        globex.requests()[0].authorization.as_deref(),
+        Some("Basic amo6Z2xvYmV4LXRva2Vu")
+    );
+
+    let profiles: Vec<String> = jiralog.worklog().iter().map(|v| v["profile"].clone()).collect();
+    assert_eq!(profiles, vec!["", "globex"]);
+}
+
+#[test]
+fn failed_commit_exits_with_jira_or_network_code() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    let jiralog = Jiralog::configured(&jira);
+
+    jiralog.stdout(&["add", "NOPE-1", "1h"]);
+    assert_eq!(jiralog.run(&["commit"]).status.code(), Some(6));
+
+    jiralog.write_config("user=jj\ntoken=secret-token\njira_url=http://127.0.0.1:9\nmax_retries=0\n");
+    assert_eq!(jiralog.run(&["commit"]).status.code(), Some(5));
+}
+
+#[test]
+fn commit_strict_refuses_overlapping_items() {
+    let jira = FakeJira::start(&["ABC-1", "ABC-2"]);
+    let jiralog = Jiralog::configured(&jira);
+
+    jiralog.stdout(&["add", "ABC-1", "2h", "--started-date", "2024-12-07T09:00"]);
+    jiralog.stdout(&["commit"]);
+    jiralog.stdout(&["add", "ABC-2", "1h", "--started-date", "2024-12-07T10:00"]);
+
+    let output = jiralog.run(&["commit", "--strict"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("1 overlapping items"));
+    assert_eq!(jira.worklogs().len(), 1);
+
+    let dry_run = jiralog.json(&["commit", "--dry-run"]);
+    assert_eq!(dry_run["overlaps"][0]["second"]["ticket"], "ABC-2");
+
+    let output = jiralog.run(&["commit"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("Warning: "));
+    assert_eq!(jira.worklogs().len(), 2);
+}
+
+#[test]
+fn commit_retries_throttled_post_but_not_gateway_errors() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    let jiralog = Jiralog::configured(&jira);
+    let envs = [("JIRALOG_MAX_RETRIES", "2"), ("JIRALOG_RETRY_BACKOFF_MS", "0")];
+    let posts = |jira: &FakeJira| jira.requests().iter().filter(|v| v.method == "POST").count();
+
+    jiralog.stdout(&["add", "ABC-1", "1h"]);
+    jira.fail_next(&[429]);
+    let output = jiralog.run_with(&["commit"], &envs, "");
+    assert!(output.status.success(), "{}", String::from_utf8(output.stderr).unwrap());
+    assert_eq!(jira.requests()[0].method, "POST");
+    assert_eq!(posts(&jira), 2);
+    assert_eq!(jira.worklogs().len(), 1);
+
+    // Worklog may have been created behind a failing gateway
+    jiralog.stdout(&["add", "ABC-1", "2h", "--started-date", "2024-12-07T09:00"]);
+    jira.fail_next(&[502]);
+    let output = jiralog.run_with(&["commit"], &envs, "");
+    assert!(!output.status.success());
+    assert_eq!(posts(&jira), 3);
+    assert_eq!(jira.worklogs().len(), 1);
+}
+
+#[test]
+fn edit_and_rm_refuse_committed_item_without_jira_id() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    let jiralog = Jiralog::configured(&jira);
+    std::fs::write(
+        jiralog.worklog_path(),
+        "ticket,time_spent,description,started_date,committed,id,remote_id,profile,minutes,paused_at,paused_seconds\n\
+         ABC-1,1h,,2024-12-07T09:00:00+02:00,true,1467c62b9c,,,60,,0\n",
+    )
+    .unwrap();
+
+    for args in [&["edit", "1467c62b9c", "-d", "Review"][..], &["rm", "1467c62b9c"], &["pop"]] {
+        let output = jiralog.run(args);
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8(output.stderr).unwrap().contains("1467c62b9c has no Jira worklog id"));
+    }
+    assert_eq!(jiralog.worklog().len(), 1);
+
+    assert!(jiralog.stdout(&["rm", "1467c62b9c", "--local"]).contains("Removed 1467c62b9c"));
+    assert!(jiralog.worklog().is_empty());
+}
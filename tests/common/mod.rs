@@ -0,0 +1,490 @@
+#![allow(dead_code)]
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+pub static ACCOUNT_ID: &str = "5b10a2844c20165700ede21g";
+
+/// In-process fake of the Jira REST api endpoints jiralog uses, api v2 and v3
+pub struct FakeJira {
+    pub url: String,
+    state: Arc<Mutex<State>>,
+    server: Arc<Server>,
+    handle: Option<JoinHandle<()>>,
+}
+
+#[derive(Default)]
+pub struct State {
+    /// Known issue keys, worklogs for other keys are rejected with 404
+    pub issues: Vec<String>,
+    pub worklogs: BTreeMap<String, FakeWorklog>,
+    pub requests: Vec<RecordedRequest>,
+    /// Tokens answered with 401
+    pub rejected_tokens: Vec<String>,
+    /// Hours per day and days per week of time tracking, Jira defaults when unset
+    pub working_time: Option<(f64, f64)>,
+    /// Statuses answered to next requests before handling them, 429 with Retry-After
+    pub failures: Vec<u16>,
+    /// Server or data center without REST api 3, answered with 404
+    pub api_v2_only: bool,
+    next_worklog_id: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct FakeWorklog {
+    pub id: String,
+    pub issue_key: String,
+    pub time_spent: String,
+    pub started: String,
+    pub comment: Value,
+    pub author: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub authorization: Option<String>,
+    pub body: Option<Value>,
+}
+
+impl FakeJira {
+    pub fn start(issues: &[&str]) -> Self {
+        let server = Arc::new(Server::http("127.0.0.1:0").expect("Start fake Jira"));
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+
+        let state = Arc::new(Mutex::new(State {
+            issues: issues.iter().map(|v| v.to_string()).collect(),
+            next_worklog_id: 10000,
+            ..State::default()
+        }));
+
+        let handle = {
+            let server = Arc::clone(&server);
+            let state = Arc::clone(&state);
+
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    handle_request(&state, request);
+                }
+            })
+        };
+
+        FakeJira {
+            url,
+            state,
+            server,
+            handle: Some(handle),
+        }
+    }
+
+    pub fn worklogs(&self) -> Vec<FakeWorklog> {
+        self.state.lock().unwrap().worklogs.values().cloned().collect()
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Answer requests authorized with token with 401
+    pub fn reject_token(&self, token: &str) {
+        self.state.lock().unwrap().rejected_tokens.push(token.to_string());
+    }
+
+    pub fn set_working_time(&self, hours_per_day: f64, days_per_week: f64) {
+        self.state.lock().unwrap().working_time = Some((hours_per_day, days_per_week));
+    }
+
+    /// Behave like Jira server or data center, REST api 3 is not found
+    pub fn serve_api_v2_only(&self) {
+        self.state.lock().unwrap().api_v2_only = true;
+    }
+
+    /// Answer next requests with given statuses, one status per request
+    pub fn fail_next(&self, statuses: &[u16]) {
+        self.state.lock().unwrap().failures.extend_from_slice(statuses);
+    }
+
+    /// Worklog logged outside jiralog, for example from Jira web UI
+    pub fn add_worklog(&self, issue_key: &str, time_spent: &str, started: &str, author: &str) -> String {
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id();
+
+        state.worklogs.insert(
+            id.clone(),
+            FakeWorklog {
+                id: id.clone(),
+                issue_key: issue_key.to_string(),
+                time_spent: time_spent.to_string(),
+                started: started.to_string(),
+                comment: json!(""),
+                author: author.to_string(),
+            },
+        );
+
+        id
+    }
+}
+
+impl Drop for FakeJira {
+    fn drop(&mut self) {
+        self.server.unblock();
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl State {
+    fn next_id(&mut self) -> String {
+        self.next_worklog_id += 1;
+        self.next_worklog_id.to_string()
+    }
+
+    fn issue_id(&self, key: &str) -> Option<String> {
+        self.issues
+            .iter()
+            .position(|v| v == key)
+            .map(|index| (20000 + index).to_string())
+    }
+
+    fn issue_key(&self, id_or_key: &str) -> Option<String> {
+        self.issues
+            .iter()
+            .enumerate()
+            .find(|(index, key)| *key == id_or_key || (20000 + index).to_string() == id_or_key)
+            .map(|(_, key)| key.clone())
+    }
+}
+
+fn handle_request(state: &Mutex<State>, mut request: Request) {
+    let mut body = String::new();
+    let _ = request.as_reader().read_to_string(&mut body);
+    let body: Option<Value> = serde_json::from_str(&body).ok();
+
+    let method = request.method().clone();
+    let url = request.url().to_string();
+    let path = url.split('?').next().unwrap_or_default().to_string();
+    let authorization = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .map(|h| h.value.to_string());
+
+    let mut state = state.lock().unwrap();
+
+    let rejected = authorization
+        .as_deref()
+        .map(token_from_authorization)
+        .is_some_and(|token| state.rejected_tokens.contains(&token));
+
+    state.requests.push(RecordedRequest {
+        method: method.to_string(),
+        path: path.clone(),
+        authorization,
+        body: body.clone(),
+    });
+
+    if rejected {
+        let _ = request.respond(Response::empty(401));
+        return;
+    }
+
+    if !state.failures.is_empty() {
+        let status = state.failures.remove(0);
+        let response = Response::empty(status);
+        let _ = match status {
+            429 => request.respond(response.with_header(Header::from_bytes("Retry-After", "0").unwrap())),
+            _ => request.respond(response),
+        };
+        return;
+    }
+
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+
+    if state.api_v2_only && segments.starts_with(&["rest", "api", "3"]) {
+        let _ = request.respond(Response::empty(404));
+        return;
+    }
+
+    let (status, response) = match (&method, segments.as_slice()) {
+        (Method::Get, ["rest", "api", _, "myself"]) => (
+            200,
+            json!({ "accountId": ACCOUNT_ID, "key": "jj", "name": "jj", "displayName": "Jay Jay" }),
+        ),
+        (Method::Get, ["rest", "api", _, "configuration"]) => {
+            let (hours_per_day, days_per_week) = state.working_time.unwrap_or((8.0, 5.0));
+
+            (
+                200,
+                json!({
+                    "timeTrackingEnabled": true,
+                    "timeTrackingConfiguration": {
+                        "workingHoursPerDay": hours_per_day,
+                        "workingDaysPerWeek": days_per_week,
+                        "timeFormat": "pretty",
+                        "defaultUnit": "minute",
+                    },
+                }),
+            )
+        }
+        (Method::Get, ["rest", "api", _, "issue", id_or_key]) => match state.issue_key(id_or_key) {
+            Some(key) => (200, json!({ "id": state.issue_id(&key), "key": key })),
+            None => not_found(),
+        },
+        (Method::Post, ["rest", "api", _, "issue", key, "worklog"]) => {
+            match (state.issue_id(key), body) {
+                (Some(_), Some(body)) => {
+                    let id = state.next_id();
+                    let worklog = FakeWorklog {
+                        id: id.clone(),
+                        issue_key: key.to_string(),
+                        time_spent: body["timeSpent"].as_str().unwrap_or_default().to_string(),
+                        started: body["started"].as_str().unwrap_or_default().to_string(),
+                        comment: body["comment"].clone(),
+                        author: ACCOUNT_ID.to_string(),
+                    };
+                    state.worklogs.insert(id.clone(), worklog);
+
+                    (201, json!({ "id": id }))
+                }
+                (None, _) => not_found(),
+                (_, None) => bad_request(),
+            }
+        }
+        (Method::Put, ["rest", "api", _, "issue", key, "worklog", id]) => {
+            match (state.worklogs.get_mut(*id), body) {
+                (Some(worklog), Some(body)) if worklog.issue_key == *key => {
+                    worklog.time_spent = body["timeSpent"].as_str().unwrap_or_default().to_string();
+                    worklog.started = body["started"].as_str().unwrap_or_default().to_string();
+                    worklog.comment = body["comment"].clone();
+
+                    (200, json!({ "id": id }))
+                }
+                (Some(_), None) => bad_request(),
+                _ => not_found(),
+            }
+        }
+        (Method::Delete, ["rest", "api", _, "issue", key, "worklog", id]) => {
+            match state.worklogs.get(*id) {
+                Some(worklog) if worklog.issue_key == *key => {
+                    state.worklogs.remove(*id);
+                    (204, Value::Null)
+                }
+                _ => not_found(),
+            }
+        }
+        (Method::Get, ["rest", "api", _, "worklog", "updated"]) => {
+            let values: Vec<Value> = state
+                .worklogs
+                .keys()
+                .map(|id| json!({ "worklogId": id.parse::<i64>().unwrap(), "updatedTime": 0 }))
+                .collect();
+
+            (200, json!({ "values": values, "since": 0, "until": 0, "lastPage": true }))
+        }
+        (Method::Post, ["rest", "api", _, "worklog", "list"]) => {
+            let ids: Vec<String> = body
+                .as_ref()
+                .and_then(|v| v["ids"].as_array().cloned())
+                .unwrap_or_default()
+                .iter()
+                .map(|v| v.as_str().map(String::from).unwrap_or_else(|| v.to_string()))
+                .collect();
+
+            let worklogs: Vec<Value> = ids
+                .iter()
+                .filter_map(|id| state.worklogs.get(id))
+                .map(|v| {
+                    json!({
+                        "id": v.id,
+                        "issueId": state.issue_id(&v.issue_key),
+                        "author": { "accountId": v.author },
+                        "started": v.started,
+                        "timeSpent": v.time_spent,
+                        "comment": v.comment,
+                    })
+                })
+                .collect();
+
+            (200, json!(worklogs))
+        }
+        _ => not_found(),
+    };
+
+    let response = match response {
+        Value::Null => Response::from_data(Vec::new()),
+        value => Response::from_data(value.to_string().into_bytes()).with_header(
+            Header::from_bytes("Content-Type", "application/json").unwrap(),
+        ),
+    };
+
+    let _ = request.respond(response.with_status_code(status));
+}
+
+static WORKLOG_HEADER: &str =
+    "ticket,time_spent,description,started_date,committed,id,remote_id,profile,minutes,paused_at,paused_seconds,resumed_at";
+
+fn current_row(ticket: &str, minutes_ago: i64) -> String {
+    let started = chrono::Local::now().fixed_offset() - chrono::TimeDelta::minutes(minutes_ago) - chrono::TimeDelta::seconds(5);
+
+    format!("{},current,,{},false,{:010x},,,,,0,\n", ticket, started.to_rfc3339(), started.timestamp())
+}
+
+/// Token of basic or bearer authorization header
+fn token_from_authorization(authorization: &str) -> String {
+    match authorization.split_once(' ') {
+        Some(("Basic", credentials)) => BASE64_STANDARD
+            .decode(credentials)
+            .ok()
+            .and_then(|v| String::from_utf8(v).ok())
+            .and_then(|v| v.split_once(':').map(|(_, token)| token.to_string()))
+            .unwrap_or_default(),
+        Some((_, token)) => token.to_string(),
+        None => String::new(),
+    }
+}
+
+fn not_found() -> (u16, Value) {
+    (
+        404,
+        json!({ "errorMessages": ["Issue does not exist or you do not have permission to see it."], "errors": {} }),
+    )
+}
+
+fn bad_request() -> (u16, Value) {
+    (400, json!({ "errorMessages": [], "errors": { "body": "Invalid request payload" } }))
+}
+
+/// Jiralog binary running against isolated temporary JIRALOG_HOME
+pub struct Jiralog {
+    pub home: TempDir,
+}
+
+impl Jiralog {
+    pub fn new() -> Self {
+        Jiralog {
+            home: tempfile::tempdir().expect("Create temporary JIRALOG_HOME"),
+        }
+    }
+
+    /// Jiralog configured against fake Jira
+    pub fn configured(jira: &FakeJira) -> Self {
+        let jiralog = Jiralog::new();
+        jiralog.write_config(&format!(
+            "user=jj\ntoken=secret-token\njira_url={}\nmax_retries=0\n",
+            jira.url
+        ));
+
+        jiralog
+    }
+
+    pub fn write_config(&self, properties: &str) {
+        fs::write(self.home.path().join("jiralog.properties"), properties).unwrap();
+    }
+
+    /// Named profile configured against fake Jira
+    pub fn write_profile_config(&self, profile: &str, jira: &FakeJira) {
+        fs::write(
+            self.home.path().join(format!("jiralog-{}.properties", profile)),
+            format!("user=jj\ntoken={}-token\njira_url={}\nmax_retries=0\n", profile, jira.url),
+        )
+        .unwrap();
+    }
+
+    pub fn worklog_path(&self) -> PathBuf {
+        self.home.path().join("worklog.csv")
+    }
+
+    /// Worklog with only current item of ticket, started minutes ago
+    pub fn write_current(&self, ticket: &str, minutes_ago: i64) {
+        fs::write(
+            self.worklog_path(),
+            format!("{}\n{}", WORKLOG_HEADER, current_row(ticket, minutes_ago)),
+        )
+        .unwrap();
+    }
+
+    /// Worklog with only current item, paused for paused seconds and paused since minutes ago if given
+    pub fn write_paused_current(&self, ticket: &str, minutes_ago: i64, paused_minutes_ago: Option<i64>, paused_seconds: i64) {
+        let paused_at = paused_minutes_ago
+            .map(|v| (chrono::Local::now().fixed_offset() - chrono::TimeDelta::minutes(v)).to_rfc3339())
+            .unwrap_or_default();
+        let row = current_row(ticket, minutes_ago).replace(",,,,0,\n", &format!(",,,{},{},\n", paused_at, paused_seconds));
+
+        fs::write(self.worklog_path(), format!("{}\n{}", WORKLOG_HEADER, row)).unwrap();
+    }
+
+    /// Current item of ticket started minutes ago after existing items
+    pub fn append_current(&self, ticket: &str, minutes_ago: i64) {
+        let worklog = fs::read_to_string(self.worklog_path()).unwrap();
+        fs::write(self.worklog_path(), format!("{}{}", worklog, current_row(ticket, minutes_ago))).unwrap();
+    }
+
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.run_with(args, &[], "")
+    }
+
+    /// Run with extra environment variables and input piped to stdin
+    pub fn run_with(&self, args: &[&str], envs: &[(&str, &str)], input: &str) -> Output {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_jiralog"))
+            .args(args)
+            .env("JIRALOG_HOME", self.home.path())
+            // Editor exiting without changes commits worklog as is
+            .env("EDITOR", "true")
+            .envs(envs.iter().copied())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Run jiralog");
+
+        child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+        child.wait_with_output().expect("Run jiralog")
+    }
+
+    pub fn stdout(&self, args: &[&str]) -> String {
+        String::from_utf8(self.run(args).stdout).unwrap()
+    }
+
+    pub fn stderr(&self, args: &[&str]) -> String {
+        String::from_utf8(self.run(args).stderr).unwrap()
+    }
+
+    /// Document printed with `--output json`
+    pub fn json(&self, args: &[&str]) -> Value {
+        let output = self.run(&[args, &["--output", "json"]].concat());
+
+        serde_json::from_slice(&output.stdout).expect("JSON document on stdout")
+    }
+
+    /// Worklog rows from `show --stdout` by column name
+    pub fn worklog(&self) -> Vec<HashMap<String, String>> {
+        let output = self.stdout(&["show", "--stdout"]);
+
+        csv::Reader::from_reader(output.as_bytes())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    /// Id of item from output like "Added 1a2b3c4d5e: ticket=..."
+    pub fn added_id(output: &str) -> String {
+        output
+            .split_whitespace()
+            .nth(1)
+            .map(|v| v.trim_end_matches(':').to_string())
+            .expect("Item id in output")
+    }
+}
@@ -0,0 +1,248 @@
+mod common;
+
+use std::fs;
+
+use common::{FakeJira, Jiralog, ACCOUNT_ID};
+
+static ENVS: [(&str, &str); 1] = [("JIRALOG_TOKEN_STORE", "properties")];
+
+#[test]
+fn configure_verifies_credentials_and_writes_profile() {
+    let jira = FakeJira::start(&[]);
+    let jiralog = Jiralog::new();
+
+    // Profile, cloud instance, url, user, token and editor
+    let input = format!("acme\n\n{}/\njj\nsecret-token\nvim\n", jira.url);
+    let output = jiralog.run_with(&["configure"], &ENVS, &input);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains(&format!("Authenticated as Jay Jay ({})", ACCOUNT_ID)), "{}", stdout);
+
+    let properties = fs::read_to_string(jiralog.home.path().join("jiralog-acme.properties")).unwrap();
+    assert!(properties.contains(&format!("jira_url={}\n", jira.url.replace(':', "\\:"))), "{}", properties);
+    assert!(properties.contains("editor=vim"), "{}", properties);
+    assert!(properties.contains("token=secret-token"), "{}", properties);
+}
+
+#[test]
+fn configure_prompts_again_for_rejected_credentials() {
+    let jira = FakeJira::start(&[]);
+    jira.reject_token("typo-token");
+    let jiralog = Jiralog::new();
+
+    let input = format!("\n\n{}\njj\ntypo-token\n\nsecret-token\n\n", jira.url);
+    let output = jiralog.run_with(&["configure"], &ENVS, &input);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("Jira rejected user or token, try again"), "{}", stdout);
+    assert!(stdout.contains("All good!"), "{}", stdout);
+
+    let properties = fs::read_to_string(jiralog.home.path().join("jiralog.properties")).unwrap();
+    assert!(properties.contains("token=secret-token"), "{}", properties);
+}
+
+#[test]
+fn configure_falls_back_to_api_2_for_jira_server() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    jira.serve_api_v2_only();
+    let jiralog = Jiralog::new();
+
+    let input = format!("\n\n{}\njj\nsecret-token\n\n", jira.url);
+    let output = jiralog.run_with(&["configure"], &ENVS, &input);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("Authenticated as Jay Jay"), "{}", stdout);
+    assert!(!stdout.contains("Jira not found"), "{}", stdout);
+    assert_eq!(jiralog.stdout(&["configure", "get", "api_version"]), "2\n");
+
+    jiralog.stdout(&["add", "ABC-1", "1h"]);
+    jiralog.stdout(&["commit"]);
+    assert_eq!(jira.worklogs().len(), 1);
+}
+
+#[test]
+fn configure_gives_up_on_unknown_jira() {
+    let jira = FakeJira::start(&[]);
+    let jiralog = Jiralog::new();
+
+    let input = format!("\n\n{}/nope\njj\nsecret-token\n", jira.url);
+    let output = jiralog.run_with(&["configure"], &ENVS, &input);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(stdout.contains("Jira not found at"), "{}", stdout);
+    assert!(stderr.contains("Could not verify configuration, Jira not found at"), "{}", stderr);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(!jiralog.home.path().join("jiralog.properties").exists());
+}
+
+#[test]
+fn configure_flags_write_without_prompts() {
+    let jira = FakeJira::start(&[]);
+    let jiralog = Jiralog::new();
+
+    let url = format!("{}/", jira.url);
+    let args = ["configure", "--user", "jj", "--token", "secret-token", "--url", &url, "--editor", "vim"];
+    let output = jiralog.run_with(&args, &ENVS, "");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("Authenticated as Jay Jay"), "{}", stdout);
+    assert_eq!(jiralog.stdout(&["configure", "get", "jira_url"]), format!("{}\n", jira.url));
+    assert_eq!(jiralog.stdout(&["configure", "get", "editor"]), "vim\n");
+
+    jira.reject_token("bad-token");
+    let output = jiralog.run_with(&["configure", "--token", "bad-token"], &ENVS, "");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(stderr.contains("Jira rejected user or token"), "{}", stderr);
+    assert_eq!(output.status.code(), Some(6));
+    let properties = fs::read_to_string(jiralog.home.path().join("jiralog.properties")).unwrap();
+    assert!(properties.contains("token=secret-token"), "{}", properties);
+}
+
+#[test]
+fn configure_set_get_and_list_properties() {
+    let jiralog = Jiralog::new();
+
+    jiralog.run_with(&["configure", "set", "token", "secret-token"], &ENVS, "");
+    jiralog.stdout(&["configure", "set", "api_version", "2"]);
+
+    let stderr = jiralog.stderr(&["configure", "set", "api_version", "4"]);
+    assert!(stderr.contains("Invalid api_version 4"), "{}", stderr);
+
+    let stderr = jiralog.stderr(&["configure", "set", "colour", "red"]);
+    assert!(stderr.contains("Unknown property colour"), "{}", stderr);
+
+    assert_eq!(jiralog.stdout(&["configure", "get", "api_version"]), "2\n");
+    assert_eq!(jiralog.stdout(&["configure", "get", "token"]), "<redacted>\n");
+
+    let output = jiralog.run_with(&["configure", "list"], &[("JIRALOG_USER", "env-user")], "");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("user=env-user (JIRALOG_USER)\n"), "{}", stdout);
+    assert!(stdout.contains("token=<redacted>\n"), "{}", stdout);
+    assert!(stdout.contains("api_version=2\n"), "{}", stdout);
+}
+
+#[test]
+fn environment_overrides_properties() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    let jiralog = Jiralog::new();
+    jiralog.write_config("user=jj\ntoken_command=exit 1\njira_url=http://127.0.0.1:9\n");
+
+    jiralog.stdout(&["add", "ABC-1", "1h"]);
+
+    let envs = [
+        ("JIRALOG_JIRA_URL", jira.url.as_str()),
+        ("JIRALOG_TOKEN", "env-token"),
+        ("JIRALOG_API_VERSION", "2"),
+    ];
+    let output = jiralog.run_with(&["commit"], &envs, "");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("All done, committed 1 items"), "{}", stdout);
+
+    let request = &jira.requests()[0];
+    assert_eq!(request.path, "/rest/api/2/issue/ABC-1/worklog");
+    // jj:env-token
+    assert_eq!(request.authorization.as_deref(), Some("Basic amo6ZW52LXRva2Vu"));
+}
+
+#[test]
+fn commit_before_configure_fails_with_hint() {
+    let jiralog = Jiralog::new();
+    jiralog.stdout(&["add", "ABC-1", "1h"]);
+
+    let output = jiralog.run(&["commit"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(4));
+    assert!(stderr.contains("Profile default not configured"), "{}", stderr);
+    assert!(stderr.contains("Hint: run jiralog configure"), "{}", stderr);
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+}
+
+#[test]
+fn invalid_configuration_is_reported() {
+    let jiralog = Jiralog::new();
+    jiralog.stdout(&["add", "ABC-1", "1h"]);
+
+    jiralog.write_config("user=jj\ntoken=secret-token\njira_url=jira.example.com\n");
+    let stderr = jiralog.stderr(&["commit"]);
+    assert!(stderr.contains("Invalid jira_url jira.example.com"), "{}", stderr);
+    assert!(stderr.contains("Hint: run jiralog configure set jira_url <url>"), "{}", stderr);
+
+    jiralog.write_config("token=secret-token\njira_url=https://jira.example.com\n");
+    let output = jiralog.run(&["--profile", "default", "commit"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(4));
+    assert!(stderr.contains("Missing user for profile default"), "{}", stderr);
+}
+
+#[test]
+fn working_time_converts_days_and_weeks() {
+    let jira = FakeJira::start(&[]);
+    jira.set_working_time(7.5, 4.0);
+    let jiralog = Jiralog::configured(&jira);
+
+    let output = jiralog.stdout(&["configure", "working-time"]);
+    assert!(output.contains("7.5h per day, 4d per week"), "{}", output);
+
+    jiralog.stdout(&["add", "ABC-1", "1d"]);
+    jiralog.stdout(&["add", "ABC-1", "1w 0.5d"]);
+
+    let worklog = jiralog.worklog();
+    assert_eq!(worklog[0]["time_spent"], "7h 30m");
+    assert_eq!(worklog[1]["time_spent"], "33h 45m");
+    assert!(jiralog.stdout(&["info"]).contains("uncommitted time spent 1w 1d 3h 45m"));
+
+    let output = jiralog.run(&["configure", "set", "hours_per_day", "25"]);
+    assert_eq!(output.status.code(), Some(4));
+
+    jiralog.stdout(&["configure", "set", "hours_per_day", "6"]);
+    jiralog.stdout(&["add", "ABC-1", "1d"]);
+    assert_eq!(jiralog.worklog()[2]["time_spent"], "6h");
+}
+
+#[test]
+fn configure_takes_working_time_from_jira() {
+    let jira = FakeJira::start(&[]);
+    jira.set_working_time(7.0, 5.0);
+    let jiralog = Jiralog::new();
+
+    let url = jira.url.clone();
+    let output = jiralog.run_with(&["configure", "--url", &url, "--user", "jj", "--token", "secret-token"], &ENVS, "");
+    assert!(String::from_utf8(output.stdout).unwrap().contains("Working time from Jira: 7h per day"));
+
+    let properties = fs::read_to_string(jiralog.home.path().join("jiralog.properties")).unwrap();
+    assert!(properties.contains("hours_per_day=7\n") || properties.contains("hours_per_day=7\r\n"), "{}", properties);
+}
+
+#[test]
+fn configure_gives_up_on_invalid_configuration() {
+    let jiralog = Jiralog::new();
+
+    // Profile, then cloud instance, user and token for each attempt
+    let input = "\nbad!inst\njj\nsecret-token\nbad!inst\n\nsecret-token\nbad!inst\n\nsecret-token\nvim\n";
+    let output = jiralog.run_with(&["configure"], &ENVS, input);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(!output.status.success());
+    assert!(!stdout.contains("All good!"), "{}", stdout);
+    assert!(!jiralog.home.path().join("jiralog.properties").exists());
+}
+
+#[test]
+fn configure_clears_cloud_instance_for_jira_url() {
+    let jira = FakeJira::start(&[]);
+    let jiralog = Jiralog::new();
+    jiralog.write_config("jira_cloud_instance=acme\nuser=jj\n");
+
+    // Profile, clear cloud instance, url, user, token and editor
+    let input = format!("\n-\n{}\n\nsecret-token\n\n", jira.url);
+    let output = jiralog.run_with(&["configure"], &ENVS, &input);
+    assert!(output.status.success(), "{}", String::from_utf8(output.stdout).unwrap());
+
+    let properties = fs::read_to_string(jiralog.home.path().join("jiralog.properties")).unwrap();
+    assert!(!properties.contains("jira_cloud_instance"), "{}", properties);
+    assert!(properties.contains(&format!("jira_url={}\n", jira.url.replace(':', "\\:"))), "{}", properties);
+}
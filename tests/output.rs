@@ -0,0 +1,89 @@
+mod common;
+
+use common::{FakeJira, Jiralog};
+use serde_json::Value;
+
+#[test]
+fn json_output_of_worklog_commands() {
+    let jiralog = Jiralog::new();
+
+    let added = jiralog.json(&["add", "ABC-1", "1h30m", "-d", "Review"]);
+    assert_eq!(added["added"]["ticket"], "ABC-1");
+    assert_eq!(added["added"]["time_spent"], "1h 30m");
+    assert_eq!(added["added"]["minutes"], 90);
+    assert_eq!(added["added"]["description"], "Review");
+    assert_eq!(added["added"]["committed"], false);
+    let added_id = added["added"]["id"].as_str().unwrap().to_string();
+
+    let begun = jiralog.json(&["begin", "ABC-2"]);
+    assert_eq!(begun["previous"], Value::Null);
+    assert_eq!(begun["current"]["ticket"], "ABC-2");
+    assert_eq!(begun["current"]["time_spent"], "current");
+
+    let current = jiralog.json(&["current"]);
+    assert_eq!(current["current"]["ticket"], "ABC-2");
+    assert_eq!(current["current"]["time_spent"], "0m");
+
+    let ended = jiralog.json(&["end"]);
+    assert_eq!(ended["ended"]["ticket"], "ABC-2");
+    assert_eq!(jiralog.json(&["end"]), serde_json::json!({ "ended": null }));
+    assert_eq!(jiralog.json(&["current"]), serde_json::json!({ "current": null }));
+
+    let shown = jiralog.json(&["show"]);
+    assert_eq!(shown.as_array().unwrap().len(), 2);
+    assert_eq!(jiralog.json(&["show", "--stdout", "-t", "ABC-1"])[0]["id"], added_id.as_str());
+
+    assert_eq!(jiralog.json(&["pop"])["removed"]["ticket"], "ABC-2");
+    assert_eq!(jiralog.json(&["rm", &added_id])["removed"]["id"], added_id.as_str());
+    assert_eq!(jiralog.json(&["pop"]), serde_json::json!({ "removed": null }));
+    assert_eq!(jiralog.json(&["purge"]), serde_json::json!({ "purged": 0 }));
+
+    let info = jiralog.json(&["info"]);
+    assert_eq!(info["profile"], "default");
+    assert_eq!(info["total_items"], 0);
+}
+
+#[test]
+fn json_output_of_commit_lists_committed_and_failed() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    let jiralog = Jiralog::configured(&jira);
+
+    jiralog.stdout(&["add", "NOPE-1", "1h"]);
+    jiralog.stdout(&["add", "ABC-1", "2h"]);
+
+    let dry_run = jiralog.json(&["commit", "--dry-run"]);
+    assert_eq!(dry_run["dry_run"].as_array().unwrap().len(), 2);
+    assert!(dry_run["dry_run"][1]["request"].as_str().unwrap().contains("ABC-1"));
+
+    let output = jiralog.run(&["commit", "--output", "json"]);
+    assert_eq!(output.status.code(), Some(6));
+
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["committed"][0]["ticket"], "ABC-1");
+    assert_eq!(report["committed"][0]["committed"], true);
+    assert_eq!(report["failed"][0]["item"]["ticket"], "NOPE-1");
+    assert_eq!(report["failed"][0]["exit_code"], 6);
+
+    let error: Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert!(error["error"]["message"].as_str().unwrap().contains("1 of 2 items failed"));
+    assert_eq!(error["error"]["exit_code"], 6);
+}
+
+#[test]
+fn json_output_of_error_goes_to_stderr() {
+    let jiralog = Jiralog::new();
+
+    assert_eq!(
+        jiralog.json(&["commit"]),
+        serde_json::json!({ "committed": [], "failed": [], "overlaps": [] })
+    );
+
+    jiralog.stdout(&["add", "ABC-1", "1h"]);
+    let output = jiralog.run(&["commit", "--output", "json"]);
+    assert!(output.stdout.is_empty());
+    assert_eq!(output.status.code(), Some(4));
+
+    let error: Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["error"]["exit_code"], 4);
+    assert_eq!(error["error"]["hint"], "run jiralog configure");
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use std::fs;
+use std::process::Command;
+
+use common::Jiralog;
+
+#[test]
+fn home_flag_overrides_jiralog_home() {
+    let jiralog = Jiralog::new();
+    let other_home = tempfile::tempdir().unwrap();
+    let other_home_path = other_home.path().to_str().unwrap();
+
+    jiralog.stdout(&["--home", other_home_path, "add", "ABC-1", "1h"]);
+
+    assert!(other_home.path().join("worklog.csv").exists());
+    assert!(jiralog.worklog().is_empty());
+}
+
+#[test]
+fn legacy_home_is_moved_to_xdg_directories() {
+    let home = tempfile::tempdir().unwrap();
+    let legacy_dir = home.path().join(".jiralog");
+    fs::create_dir_all(&legacy_dir).unwrap();
+    fs::write(legacy_dir.join("jiralog.properties"), "user=jj\ntoken=t\njira_url=http://localhost\n").unwrap();
+    fs::write(legacy_dir.join("token-acme.enc"), "encrypted").unwrap();
+    fs::write(
+        legacy_dir.join("worklog.csv"),
+        "ticket,time_spent,description,started_date,committed,id\nABC-1,1h,,2024-12-07T09:00:00+02:00,false,1467c62b9c\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_jiralog"))
+        .args(["show", "--stdout"])
+        .env_remove("JIRALOG_HOME")
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path().join("config"))
+        .env("XDG_DATA_HOME", home.path().join("data"))
+        .output()
+        .unwrap();
+
+    assert!(String::from_utf8(output.stdout).unwrap().contains("1467c62b9c"));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("Moved"));
+    assert!(home.path().join("config/jiralog/jiralog.properties").exists());
+    assert!(home.path().join("data/jiralog/worklog.csv").exists());
+    assert!(home.path().join("config/jiralog/token-acme.enc").exists());
+    assert!(!legacy_dir.exists());
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::{FakeJira, Jiralog};
+
+#[test]
+fn report_by_day_and_week_marks_days_under_target() {
+    let jiralog = Jiralog::new();
+    jiralog.stdout(&["configure", "set", "daily_target", "7h 30m"]);
+
+    jiralog.stdout(&["add", "ABC-1", "8h", "--started-date", "2024-12-02T09:00"]);
+    jiralog.stdout(&["add", "ABC-2", "3h", "--started-date", "2024-12-03T09:00"]);
+    jiralog.stdout(&["add", "ABC-2", "2h", "--started-date", "2024-12-07T09:00"]);
+    jiralog.stdout(&["add", "ABC-3", "1h", "--started-date", "2024-12-09T09:00"]);
+
+    let report = jiralog.json(&["report", "--from", "2024-12-02", "--to", "2024-12-08"]);
+    assert_eq!(report["daily_target_minutes"], 450);
+    assert_eq!(report["total_minutes"], 13 * 60);
+
+    let rows = report["rows"].as_array().unwrap();
+    let keys: Vec<&str> = rows.iter().map(|v| v["key"].as_str().unwrap()).collect();
+    assert_eq!(keys, ["2024-12-02", "2024-12-03", "2024-12-04", "2024-12-05", "2024-12-06", "2024-12-07"]);
+    assert_eq!(rows[0]["under_target"], false);
+    assert_eq!(rows[1]["under_target"], true);
+    assert_eq!(rows[2]["total_minutes"], 0);
+    assert_eq!(rows[5]["target_minutes"], serde_json::Value::Null);
+    assert_eq!(rows[5]["under_target"], false);
+
+    let output = jiralog.stdout(&["report", "--from", "2024-12-02", "--to", "2024-12-10", "--group-by", "week"]);
+    assert!(output.contains("2024-W49  0m          13h          13h         37h 30m"), "{}", output);
+    assert!(output.contains("2024-W50  0m          1h           1h          15h"), "{}", output);
+    assert!(output.contains("TOTAL     0m          14h          14h"), "{}", output);
+
+    let output = jiralog.run(&["report", "--from", "2024-12-08", "--to", "2024-12-02"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn dates_use_offset_of_their_own_day_across_daylight_saving() {
+    let jiralog = Jiralog::new();
+    let tz = [("TZ", "Europe/Helsinki")];
+
+    // Winter time is +02:00 and summer time +03:00, whatever offset is in effect today
+    jiralog.run_with(&["add", "ABC-1", "1h", "--started-date", "2024-01-15 23:30"], &tz, "");
+    jiralog.run_with(&["add", "ABC-2", "1h", "--started-date", "2024-07-15 00:30"], &tz, "");
+
+    let worklog = jiralog.worklog();
+    assert_eq!(worklog[0]["started_date"], "2024-01-15T23:30:00+02:00");
+    assert_eq!(worklog[1]["started_date"], "2024-07-15T00:30:00+03:00");
+
+    for day in ["2024-01-15", "2024-07-15"] {
+        let output = jiralog.run_with(&["report", "--from", day, "--to", day, "--output", "json"], &tz, "");
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(report["total_minutes"], 60, "{}", day);
+    }
+}
+
+#[test]
+fn report_by_ticket_and_project_splits_committed() {
+    let jira = FakeJira::start(&["ABC-1", "ABC-2", "XY-1"]);
+    let jiralog = Jiralog::configured(&jira);
+
+    jiralog.stdout(&["add", "ABC-1", "1h", "--started-date", "2024-12-02T09:00"]);
+    jiralog.stdout(&["commit"]);
+    jiralog.stdout(&["add", "ABC-1", "30m", "--started-date", "2024-12-02T10:00"]);
+    jiralog.stdout(&["add", "ABC-2", "2h", "--started-date", "2024-12-03T09:00"]);
+    jiralog.stdout(&["add", "XY-1", "45m", "--started-date", "2024-12-03T11:00"]);
+
+    let report = jiralog.json(&["report", "-f", "2024-12-02", "-t", "2024-12-06", "-g", "ticket"]);
+    let rows = report["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0]["key"], "ABC-1");
+    assert_eq!(rows[0]["committed_minutes"], 60);
+    assert_eq!(rows[0]["uncommitted_minutes"], 30);
+    assert_eq!(rows[0]["target_minutes"], serde_json::Value::Null);
+    assert_eq!(report["committed_minutes"], 60);
+    assert_eq!(report["uncommitted_minutes"], 195);
+
+    let report = jiralog.json(&["report", "-f", "2024-12-02", "-t", "2024-12-06", "-g", "project"]);
+    let rows = report["rows"].as_array().unwrap();
+    assert_eq!(rows[0]["key"], "ABC");
+    assert_eq!(rows[0]["total_minutes"], 210);
+    assert_eq!(rows[1]["key"], "XY");
+
+    let output = jiralog.stdout(&["report", "-f", "2024-11-01", "-t", "2024-11-02", "-g", "ticket"]);
+    assert!(output.contains("Nothing to report between 2024-11-01 and 2024-11-02"), "{}", output);
+}
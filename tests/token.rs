@@ -0,0 +1,120 @@
+mod common;
+
+use std::fs;
+
+use common::{FakeJira, Jiralog};
+
+#[test]
+fn token_command_provides_token() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    let jiralog = Jiralog::new();
+    jiralog.write_config(&format!(
+        "user=jj\ntoken_command=echo command-token\njira_url={}\nmax_retries=0\n",
+        jira.url
+    ));
+
+    jiralog.stdout(&["add", "ABC-1", "1h"]);
+    jiralog.stdout(&["commit"]);
+
+    // jj:command-token
+    assert_eq!(
+        jira.requests()[0].authorization.as_deref(),
+        Some("Basic amo6Y29tbWFuZC10b2tlbg==")
+    );
+}
+
+#[test]
+fn failing_token_command_is_reported() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    let jiralog = Jiralog::new();
+    jiralog.write_config(&format!(
+        "user=jj\ntoken_command=echo locked >&2; exit 3\njira_url={}\n",
+        jira.url
+    ));
+
+    jiralog.stdout(&["add", "ABC-1", "1h"]);
+    let stderr = jiralog.stderr(&["commit"]);
+
+    assert!(stderr.contains("token_command exited with 3: locked"), "{}", stderr);
+    assert!(jira.requests().is_empty());
+}
+
+#[test]
+fn token_is_read_from_keyring_by_default() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    let jiralog = Jiralog::new();
+    jiralog.write_config(&format!("user=jj\njira_url={}\n", jira.url));
+
+    jiralog.stdout(&["add", "ABC-1", "1h"]);
+    let output = jiralog.run(&["commit"]);
+
+    assert_eq!(output.status.code(), Some(4));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("Token for profile default unavailable"));
+    assert!(jira.requests().is_empty());
+}
+
+#[test]
+fn configure_encrypts_token_to_file() {
+    let jira = FakeJira::start(&["ABC-1"]);
+    let jiralog = Jiralog::new();
+    let envs = [("JIRALOG_TOKEN_STORE", "file"), ("JIRALOG_PASSPHRASE", "hunter2")];
+
+    // Profile, cloud instance, url, user, token and editor
+    let input = format!("\n\n{}\njj\nsecret-token\n\n", jira.url);
+    let output = jiralog.run_with(&["configure"], &envs, &input);
+    assert!(output.status.success());
+
+    let properties = fs::read_to_string(jiralog.home.path().join("jiralog.properties")).unwrap();
+    assert!(properties.contains("token_store=file"), "{}", properties);
+    assert!(!properties.contains("secret-token"), "{}", properties);
+
+    let token_file = fs::read_to_string(jiralog.home.path().join("token.enc")).unwrap();
+    assert!(!token_file.contains("secret-token"));
+
+    jiralog.stdout(&["add", "ABC-1", "1h"]);
+
+    let stderr = String::from_utf8(
+        jiralog.run_with(&["commit"], &[("JIRALOG_PASSPHRASE", "wrong")], "").stderr,
+    )
+    .unwrap();
+    assert!(stderr.contains("Decrypting token failed"), "{}", stderr);
+
+    jiralog.run_with(&["commit"], &[("JIRALOG_PASSPHRASE", "hunter2")], "");
+
+    // jj:secret-token
+    let commit = jira.requests().into_iter().find(|v| v.method == "POST").unwrap();
+    assert_eq!(commit.authorization.as_deref(), Some("Basic amo6c2VjcmV0LXRva2Vu"));
+}
+
+#[test]
+fn set_token_follows_token_store_property() {
+    let jiralog = Jiralog::new();
+
+    jiralog.stdout(&["configure", "set", "token_store", "properties"]);
+    jiralog.stdout(&["configure", "set", "token", "secret-token"]);
+
+    let properties = fs::read_to_string(jiralog.home.path().join("jiralog.properties")).unwrap();
+    assert!(properties.contains("token_store=properties"), "{}", properties);
+    assert!(properties.contains("token=secret-token"), "{}", properties);
+    assert!(!jiralog.home.path().join("token.enc").exists());
+}
+
+#[test]
+fn configure_flags_never_prompt_for_passphrase() {
+    let jiralog = Jiralog::new();
+    let args = ["configure", "--user", "jj", "--token", "secret-token", "--url", "http://127.0.0.1:9", "--no-verify"];
+
+    let output = jiralog.run(&[&args[..], &["--token-store", "file"]].concat());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(4));
+    assert!(stderr.contains("encrypted token file needs passphrase"), "{}", stderr);
+    assert!(stderr.contains("JIRALOG_TOKEN_STORE=properties or JIRALOG_PASSPHRASE"), "{}", stderr);
+    assert!(!jiralog.home.path().join("jiralog.properties").exists());
+
+    let output = jiralog.run(&[&args[..], &["--token-store", "properties"]].concat());
+    assert!(output.status.success());
+
+    let properties = fs::read_to_string(jiralog.home.path().join("jiralog.properties")).unwrap();
+    assert!(properties.contains("token_store=properties"), "{}", properties);
+    assert!(properties.contains("token=secret-token"), "{}", properties);
+}
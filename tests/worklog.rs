@@ -0,0 +1,385 @@
+mod common;
+
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+use common::Jiralog;
+
+#[test]
+fn add_appends_uncommitted_item() {
+    let jiralog = Jiralog::new();
+
+    let output = jiralog.stdout(&["add", "ABC-1", "1h30m", "--started-date", "9:30", "-d", "Review"]);
+    assert!(output.contains("ticket=ABC-1, time spent=1h 30m"), "{}", output);
+
+    let worklog = jiralog.worklog();
+    assert_eq!(worklog.len(), 1);
+    assert_eq!(worklog[0]["ticket"], "ABC-1");
+    assert_eq!(worklog[0]["description"], "Review");
+    assert_eq!(worklog[0]["committed"], "false");
+    assert!(worklog[0]["started_date"].contains("T09:30:00"));
+}
+
+#[test]
+fn add_rejects_invalid_time_spent() {
+    let jiralog = Jiralog::new();
+
+    let error = jiralog.stderr(&["add", "ABC-1", "1x"]);
+
+    assert!(error.contains("Invalid time spent"), "{}", error);
+
+    for input in ["0m", "0h", "0:00"] {
+        let error = jiralog.stderr(&["add", "ABC-1", input]);
+        assert!(error.contains("must be above zero"), "{}", error);
+    }
+
+    for input in ["99999999999h", "9999999999w"] {
+        let error = jiralog.stderr(&["add", "ABC-1", input]);
+        assert!(error.contains("too large"), "{}", error);
+    }
+    assert!(jiralog.worklog().is_empty());
+}
+
+#[test]
+fn add_normalises_time_spent_to_jira_format() {
+    let jiralog = Jiralog::new();
+
+    for input in ["1h 30m", "1.5h", "90min", "1:30", "0.25d 1.5h"] {
+        jiralog.stdout(&["add", "ABC-1", input]);
+    }
+    jiralog.stdout(&["add", "ABC-1", "1w 2d"]);
+
+    let worklog = jiralog.worklog();
+    let time_spent: Vec<&str> = worklog.iter().map(|v| v["time_spent"].as_str()).collect();
+    assert_eq!(time_spent, vec!["1h 30m", "1h 30m", "1h 30m", "1h 30m", "3h 30m", "56h"]);
+    assert_eq!(worklog[0]["minutes"], "90");
+
+    for input in ["5m30m", "30m 1h", "1.5m", "1:75", "90"] {
+        let error = jiralog.stderr(&["add", "ABC-1", input]);
+        assert!(error.contains(&format!("Invalid time spent {}", input)), "{}", error);
+    }
+}
+
+#[test]
+fn begin_ends_previous_and_end_closes_current() {
+    let jiralog = Jiralog::new();
+
+    let first = jiralog.stdout(&["begin", "ABC-1", "-d", "First"]);
+    assert!(first.contains("Begin"), "{}", first);
+
+    let second = jiralog.stdout(&["begin", "ABC-2"]);
+    assert!(second.contains("End"), "{}", second);
+    assert!(second.contains("ticket=ABC-1"), "{}", second);
+
+    assert!(jiralog.stdout(&["current", "-f", "%ti"]).contains("ABC-2"));
+
+    let end = jiralog.stdout(&["end"]);
+    assert!(end.contains("ticket=ABC-2, time spent=1m"), "{}", end);
+    assert!(jiralog.stdout(&["end"]).contains("Nothing to end"));
+
+    let worklog = jiralog.worklog();
+    assert_eq!(worklog.len(), 2);
+    assert!(worklog.iter().all(|v| v["time_spent"] == "1m"), "minimum time spent is 1m");
+}
+
+#[test]
+fn rm_and_pop_remove_items() {
+    let jiralog = Jiralog::new();
+
+    let first = Jiralog::added_id(&jiralog.stdout(&["add", "ABC-1", "1h"]));
+    jiralog.stdout(&["add", "ABC-2", "2h"]);
+    jiralog.stdout(&["add", "ABC-3", "3h"]);
+
+    assert!(jiralog.stdout(&["pop"]).contains("ticket=ABC-3"));
+    assert!(jiralog.stdout(&["rm", &first]).contains(&format!("Removed {}", first)));
+    assert!(jiralog.stderr(&["rm", "missing"]).contains("No worklog item missing"));
+
+    let worklog = jiralog.worklog();
+    assert_eq!(worklog.len(), 1);
+    assert_eq!(worklog[0]["ticket"], "ABC-2");
+}
+
+#[test]
+fn add_waits_for_worklog_lock_and_appends() {
+    let jiralog = Jiralog::new();
+    jiralog.stdout(&["add", "ABC-1", "1h"]);
+
+    let lock = fs::OpenOptions::new()
+        .write(true)
+        .open(jiralog.home.path().join("worklog.lock"))
+        .unwrap();
+    lock.lock().unwrap();
+
+    thread::scope(|scope| {
+        let writer = scope.spawn(|| jiralog.stdout(&["add", "ABC-2", "2h"]));
+
+        thread::sleep(Duration::from_millis(500));
+        assert!(!writer.is_finished(), "second writer did not wait for lock");
+        assert_eq!(fs::read_to_string(jiralog.worklog_path()).unwrap().lines().count(), 2);
+
+        lock.unlock().unwrap();
+        assert!(writer.join().unwrap().contains("ticket=ABC-2"));
+    });
+
+    let worklog = jiralog.worklog();
+    assert_eq!(worklog.len(), 2);
+    assert_eq!(worklog[0]["ticket"], "ABC-1");
+    assert_eq!(worklog[1]["ticket"], "ABC-2");
+}
+
+#[test]
+fn add_rewrites_worklog_with_older_columns() {
+    let jiralog = Jiralog::new();
+    fs::write(
+        jiralog.worklog_path(),
+        "ticket,time_spent,description,started_date,committed,id\nABC-1,1h,,2024-12-07T09:00:00+02:00,false,1467c62b9c\n",
+    )
+    .unwrap();
+
+    jiralog.stdout(&["add", "ABC-2", "2h"]);
+
+    let worklog = jiralog.worklog();
+    assert_eq!(worklog.len(), 2);
+    assert_eq!(worklog[0]["id"], "1467c62b9c");
+    assert_eq!(worklog[1]["ticket"], "ABC-2");
+    assert!(fs::read_to_string(jiralog.worklog_path()).unwrap().starts_with("ticket,time_spent,description,started_date,committed,id,remote_id"));
+}
+
+#[test]
+fn migrate_moves_worklog_to_sqlite() {
+    let jiralog = Jiralog::new();
+
+    jiralog.stdout(&["add", "ABC-1", "1h"]);
+    jiralog.stdout(&["add", "ABC-2", "2h"]);
+
+    assert!(jiralog.stdout(&["migrate"]).contains("Migrated 2 items"));
+    assert!(!jiralog.worklog_path().exists());
+
+    jiralog.stdout(&["add", "ABC-3", "3h"]);
+    jiralog.stdout(&["pop"]);
+
+    let tickets: Vec<String> = jiralog.worklog().iter().map(|v| v["ticket"].clone()).collect();
+    assert_eq!(tickets, vec!["ABC-1", "ABC-2"]);
+}
+
+#[test]
+fn exit_code_tells_error_category() {
+    let jiralog = Jiralog::new();
+
+    assert_eq!(jiralog.run(&["add", "ABC-1", "1h"]).status.code(), Some(0));
+    assert_eq!(jiralog.run(&["add", "ABC-1", "an hour"]).status.code(), Some(2));
+    assert_eq!(jiralog.run(&["rm", "nope"]).status.code(), Some(3));
+    assert_eq!(jiralog.run(&["commit"]).status.code(), Some(4));
+}
+
+#[test]
+fn end_rounds_tracked_time_spent() {
+    let jiralog = Jiralog::new();
+    jiralog.write_config("rounding=up\nrounding_minutes=15\n");
+    jiralog.write_current("ABC-1", 7);
+
+    let end = jiralog.stdout(&["end"]);
+    assert!(end.contains("ticket=ABC-1, time spent=15m"), "{}", end);
+
+    jiralog.write_config("rounding=nearest\nrounding_minutes=15\n");
+    jiralog.write_current("ABC-1", 50);
+    jiralog.stdout(&["end"]);
+    assert_eq!(jiralog.worklog()[0]["time_spent"], "45m");
+
+    // Zero minimum would round down to empty worklog Jira rejects
+    jiralog.write_config("rounding=down\nrounding_minutes=15\nminimum_minutes=0\n");
+    jiralog.write_current("ABC-1", 7);
+    let output = jiralog.run(&["end"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("Invalid minimum_minutes 0"));
+    assert!(jiralog.stderr(&["configure", "set", "minimum_minutes", "0"]).contains("use whole number above 0"));
+}
+
+#[test]
+fn end_drops_or_merges_short_items() {
+    let jiralog = Jiralog::new();
+    jiralog.write_config("minimum_minutes=15\nshort_entries=drop\n");
+    jiralog.write_current("ABC-1", 7);
+
+    let end = jiralog.stdout(&["end"]);
+    assert!(end.contains("Dropped"), "{}", end);
+    assert!(jiralog.worklog().is_empty());
+
+    jiralog.write_config("minimum_minutes=15\nshort_entries=merge\n");
+    let previous = Jiralog::added_id(&jiralog.stdout(&["add", "ABC-1", "30m"]));
+    jiralog.append_current("ABC-1", 7);
+
+    let end = jiralog.stdout(&["end"]);
+    assert!(end.contains(&format!("into {}", previous)), "{}", end);
+
+    let worklog = jiralog.worklog();
+    assert_eq!(worklog.len(), 1);
+    assert_eq!(worklog[0]["time_spent"], "37m");
+
+    // Nothing to merge into, kept at minimum
+    jiralog.append_current("ABC-2", 7);
+    jiralog.stdout(&["end"]);
+    assert_eq!(jiralog.worklog()[1]["time_spent"], "15m");
+}
+
+#[test]
+fn pause_and_resume_current_item() {
+    let jiralog = Jiralog::new();
+
+    assert!(jiralog.stdout(&["pause"]).contains("Nothing to pause"));
+
+    jiralog.stdout(&["begin", "ABC-1", "-d", "Review"]);
+    assert!(jiralog.stdout(&["pause"]).contains("Paused"));
+    assert_eq!(jiralog.run(&["pause"]).status.code(), Some(2));
+    assert!(jiralog.stdout(&["current"]).contains("(paused)"));
+
+    assert!(jiralog.stdout(&["resume"]).contains("Resumed"));
+    assert_eq!(jiralog.run(&["resume"]).status.code(), Some(2));
+
+    let end = jiralog.stdout(&["end"]);
+    assert!(end.contains("description=Review"), "{}", end);
+    assert!(jiralog.stdout(&["resume"]).contains("Nothing to resume"));
+}
+
+#[test]
+fn end_subtracts_paused_time() {
+    let jiralog = Jiralog::new();
+
+    jiralog.write_paused_current("ABC-1", 60, None, 600);
+    jiralog.stdout(&["end"]);
+    assert_eq!(jiralog.worklog()[0]["time_spent"], "50m");
+
+    // Ended while paused, time after pause is not counted
+    jiralog.write_paused_current("ABC-1", 60, Some(20), 600);
+    jiralog.stdout(&["end"]);
+    assert_eq!(jiralog.worklog()[0]["time_spent"], "30m");
+}
+
+#[test]
+fn end_refuses_time_before_last_resume() {
+    let jiralog = Jiralog::new();
+
+    // Started 60m ago, paused 20m until resumed 20m ago
+    jiralog.write_paused_current("ABC-1", 60, None, 1200);
+    let resumed_at = (chrono::Local::now().fixed_offset() - chrono::TimeDelta::minutes(20)).to_rfc3339();
+    let worklog = fs::read_to_string(jiralog.worklog_path()).unwrap();
+    fs::write(jiralog.worklog_path(), worklog.replace(",1200,\n", &format!(",1200,{}\n", resumed_at))).unwrap();
+
+    let output = jiralog.run(&["end", "--at", "-30m"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("was resumed at"));
+
+    let end = jiralog.stdout(&["end", "--at", "-10m"]);
+    assert!(end.contains("ticket=ABC-1, time spent=30m"), "{}", end);
+}
+
+#[test]
+fn begin_and_end_at_given_times() {
+    let jiralog = Jiralog::new();
+
+    jiralog.stdout(&["begin", "ABC-1", "--at", "-50m"]);
+    let second = jiralog.stdout(&["begin", "ABC-2", "--at", "20 minutes ago"]);
+    assert!(second.contains("ticket=ABC-1, time spent=30m"), "{}", second);
+
+    let output = jiralog.run(&["end", "--at", "-30m"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("is before start"));
+
+    assert_eq!(jiralog.run(&["end", "--at", "tomorrow"]).status.code(), Some(2));
+
+    let end = jiralog.stdout(&["end", "--at", "-5m"]);
+    assert!(end.contains("ticket=ABC-2, time spent=15m"), "{}", end);
+
+    jiralog.stdout(&["begin", "ABC-3", "--at", "yesterday 17:30"]);
+    let worklog = jiralog.worklog();
+    assert!(worklog[2]["started_date"].contains("T17:30:00"), "{}", worklog[2]["started_date"]);
+}
+
+#[test]
+fn add_accepts_natural_dates() {
+    use chrono::{Datelike, Local, TimeDelta};
+
+    let jiralog = Jiralog::new();
+    jiralog.stdout(&["configure", "set", "day_start", "8:30"]);
+
+    let today = Local::now().date_naive();
+    let friday = today.weekday().num_days_from_monday() as i64 + 7 - 4;
+    let last_friday = today - TimeDelta::days(if friday % 7 == 0 { 7 } else { friday % 7 });
+    let monday = today - TimeDelta::days(today.weekday().num_days_from_monday() as i64);
+    let expected = [
+        ("yesterday 9:00", format!("{}T09:00:00", today - TimeDelta::days(1))),
+        ("mon 14:00", format!("{}T14:00:00", monday)),
+        ("last friday", format!("{}T08:30:00", last_friday)),
+        ("2024-12-07", "2024-12-07T08:30:00".to_string()),
+        ("-3d 10:00", format!("{}T10:00:00", today - TimeDelta::days(3))),
+        ("2024-12-07T10:11:12", "2024-12-07T10:11:12".to_string()),
+    ];
+
+    for (started_date, _) in &expected {
+        jiralog.stdout(&["add", "ABC-1", "1h", "--started-date", started_date]);
+    }
+
+    let worklog = jiralog.worklog();
+    for (item, (started_date, date)) in worklog.iter().zip(&expected) {
+        assert!(item["started_date"].starts_with(date.as_str()), "{} {}", started_date, item["started_date"]);
+    }
+
+    let output = jiralog.run(&["add", "ABC-1", "1h", "--started-date", "next week"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Invalid date next week") && stderr.contains("'last friday'"), "{}", stderr);
+}
+
+#[test]
+fn add_warns_about_overlaps_and_strict_refuses() {
+    let jiralog = Jiralog::new();
+
+    jiralog.stdout(&["add", "ABC-1", "3h", "--started-date", "2024-12-07T09:00"]);
+
+    let output = jiralog.run(&["add", "ABC-2", "2h", "--started-date", "2024-12-07T10:00"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("ticket=ABC-2, 2024-12-07 10:00-12:00 overlaps"), "{}", stderr);
+    assert!(stderr.contains("ticket=ABC-1, 2024-12-07 09:00-12:00 by 2h"), "{}", stderr);
+
+    let output = jiralog.run(&["add", "ABC-3", "1h", "--started-date", "2024-12-07T11:30", "--strict"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("2 overlapping items"));
+    assert_eq!(jiralog.worklog().len(), 2);
+
+    let added = jiralog.json(&["add", "ABC-3", "1h", "--started-date", "2024-12-07T12:00", "--strict"]);
+    assert_eq!(added["added"]["ticket"], "ABC-3");
+    assert_eq!(added["overlaps"], serde_json::json!([]));
+}
+
+#[test]
+fn check_lists_overlaps_and_gaps_of_working_day() {
+    let jiralog = Jiralog::new();
+
+    jiralog.stdout(&["add", "ABC-1", "2h", "--started-date", "2024-12-05T09:00"]);
+    jiralog.stdout(&["add", "ABC-2", "1h", "--started-date", "2024-12-05T10:30"]);
+    jiralog.stdout(&["add", "ABC-3", "3h", "--started-date", "2024-12-05T13:00"]);
+    jiralog.stdout(&["add", "ABC-4", "1h", "--started-date", "2024-12-06T13:00"]);
+
+    let output = jiralog.stdout(&["check", "2024-12-05"]);
+    assert!(output.contains("ticket=ABC-2, 2024-12-05 10:30-11:30 overlaps"), "{}", output);
+    assert!(output.contains("Gap 2024-12-05 11:30-13:00, 1h 30m"), "{}", output);
+    assert!(output.contains("Gap 2024-12-05 16:00-17:00, 1h"), "{}", output);
+
+    let report = jiralog.json(&["check", "2024-12-05"]);
+    assert_eq!(report["day"], "2024-12-05");
+    assert_eq!(report["overlaps"].as_array().unwrap().len(), 1);
+    assert_eq!(report["overlaps"][0]["first"]["ticket"], "ABC-1");
+    assert_eq!(report["overlaps"][0]["minutes"], 30);
+    assert_eq!(report["gaps"].as_array().unwrap().len(), 2);
+
+    jiralog.stdout(&["configure", "set", "day_start", "13:00"]);
+    jiralog.stdout(&["configure", "set", "hours_per_day", "1"]);
+    assert!(jiralog.stdout(&["check", "2024-12-06"]).contains("No overlaps or gaps on 2024-12-06"));
+
+    // Weekend is outside 5 day working week unless week is longer
+    assert!(jiralog.stdout(&["check", "2024-12-08"]).contains("No overlaps or gaps on 2024-12-08"));
+    jiralog.stdout(&["configure", "set", "days_per_week", "7"]);
+    assert!(jiralog.stdout(&["check", "2024-12-08"]).contains("Gap 2024-12-08 13:00-14:00, 1h"));
+}